@@ -1,5 +1,4 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use kseq;
 use needletail::parser::{FastaReader, FastqReader, FastxReader};
 use std::{io::Cursor, iter};
 
@@ -16,9 +15,9 @@ fn simulate_fastq(total: usize) -> Vec<u8> {
         if sum + seq_len > total {
             seq_len = total - sum;
         }
-        data.extend(iter::repeat(b'A').take(seq_len));
+        data.extend(iter::repeat_n(b'A', seq_len));
         data.extend([b'\n', b'+', b'\n']);
-        data.extend(iter::repeat(b'!').take(seq_len));
+        data.extend(iter::repeat_n(b'!', seq_len));
         data.push(b'\n');
         sum += seq_len;
         seq_len += 2;
@@ -44,10 +43,10 @@ fn simulate_fasta(total: usize) -> Vec<u8> {
             seq_len = total - sum;
         }
         for _ in 0..seq_len / 100 {
-            data.extend(iter::repeat(b'A').take(100));
+            data.extend(iter::repeat_n(b'A', 100));
             data.push(b'\n');
         }
-        data.extend(iter::repeat(b'A').take(seq_len % 100));
+        data.extend(iter::repeat_n(b'A', seq_len % 100));
         data.push(b'\n');
         sum += seq_len;
         seq_len += 2;
@@ -59,6 +58,24 @@ fn simulate_fasta(total: usize) -> Vec<u8> {
     data
 }
 
+// simulate long records (e.g. chromosomes) wrapped at 60 bases
+fn simulate_long_fasta(total: usize, n_records: usize) -> Vec<u8> {
+    let mut data: Vec<u8> = vec![];
+    let seq_len = total / n_records;
+    for n in 1..=n_records {
+        data.push(b'>');
+        data.extend(format!("chr{}", n).as_bytes());
+        data.push(b'\n');
+        for _ in 0..seq_len / 60 {
+            data.extend(iter::repeat_n(b'A', 60));
+            data.push(b'\n');
+        }
+        data.extend(iter::repeat_n(b'A', seq_len % 60));
+        data.push(b'\n');
+    }
+    data
+}
+
 fn bench_fasta_file(c: &mut Criterion) {
     let n_total = 1_000_000_000;
     let data = simulate_fasta(n_total);
@@ -123,5 +140,44 @@ fn bench_fastq_file(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(io, bench_fastq_file, bench_fasta_file);
+fn bench_long_fasta_file(c: &mut Criterion) {
+    let n_total = 1_000_000_000;
+    let data = simulate_long_fasta(n_total, 5);
+
+    let mut group = c.benchmark_group("long FASTA parsing(1GB)");
+    group.sample_size(30);
+
+    group.bench_function("kseq", |bench| {
+        bench.iter(|| {
+            let mut n_bases = 0;
+            let mut records = kseq::parse_reader(Cursor::new(&data)).unwrap();
+            while let Ok(Some(record)) = records.iter_record() {
+                n_bases += record.seq().len() as u64;
+            }
+            assert_eq!(n_bases, n_total as u64);
+        });
+    });
+
+    group.bench_function("kseq lines", |bench| {
+        bench.iter(|| {
+            let mut n_bases = 0;
+            let mut records = kseq::parse_reader(Cursor::new(&data)).unwrap();
+            while let Ok(Some(mut record)) = records.iter_record_lines() {
+                while let Ok(Some(lines)) = record.next_lines() {
+                    n_bases += lines.map(|l| l.len() as u64).sum::<u64>();
+                }
+            }
+            assert_eq!(n_bases, n_total as u64);
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    io,
+    bench_fastq_file,
+    bench_fasta_file,
+    bench_long_fasta_file
+);
 criterion_main!(io);
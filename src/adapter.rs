@@ -0,0 +1,170 @@
+//! Adapter detection and trimming
+//!
+//! Adapters are searched at the 3' end of reads, allowing a partial overlap at the read end
+//! and a number of mismatches. For paired-end reads, an insert shorter than the read length
+//! is also detected from the overlap of the two mates, which trims the adapters even if they
+//! are not in the known adapter list.
+
+use crate::record::OwnedFastx;
+use crate::seq::revcomp;
+use crate::transform::Transform;
+
+/// the Illumina TruSeq adapter
+pub const TRUSEQ: &str = "AGATCGGAAGAGC";
+/// the Illumina Nextera adapter
+pub const NEXTERA: &str = "CTGTCTCTTATACACATCT";
+/// the Illumina small RNA 3' adapter
+pub const SMALL_RNA: &str = "TGGAATTCTCGG";
+
+/// an adapter sequence with a name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adapter {
+    /// adapter name
+    pub name: String,
+    /// adapter sequence, in upper case
+    pub seq: String,
+}
+
+impl Adapter {
+    /// create a new Adapter
+    pub fn new(name: &str, seq: &str) -> Self {
+        Adapter {
+            name: name.to_string(),
+            seq: seq.to_ascii_uppercase(),
+        }
+    }
+
+    /// get the known Illumina adapters
+    pub fn known() -> Vec<Adapter> {
+        vec![
+            Adapter::new("TruSeq", TRUSEQ),
+            Adapter::new("Nextera", NEXTERA),
+            Adapter::new("SmallRNA", SMALL_RNA),
+        ]
+    }
+}
+
+/// an adapter trimmer with per-adapter hit counts
+#[derive(Debug, Clone)]
+pub struct AdapterTrimmer {
+    adapters: Vec<Adapter>,
+    hits: Vec<u64>,
+    overlap_hits: u64,
+    /// the minimum overlap between a read and an adapter to trim
+    pub min_overlap: usize,
+    /// the minimum overlap between mates to detect an insert shorter than the reads, which
+    /// is much longer than `min_overlap` as mates are not known sequences
+    pub min_pair_overlap: usize,
+    /// the maximum number of mismatches, an overlap of `n` bases allows at most `n / 8` mismatches
+    pub max_mismatches: usize,
+}
+
+impl Default for AdapterTrimmer {
+    fn default() -> Self {
+        Self::new(Adapter::known())
+    }
+}
+
+impl AdapterTrimmer {
+    /// create an AdapterTrimmer with the given adapters
+    pub fn new(adapters: Vec<Adapter>) -> Self {
+        AdapterTrimmer {
+            hits: vec![0; adapters.len()],
+            adapters,
+            overlap_hits: 0,
+            min_overlap: 4,
+            min_pair_overlap: 30,
+            max_mismatches: 2,
+        }
+    }
+
+    /// get the adapters and their hit counts
+    pub fn hits(&self) -> impl Iterator<Item = (&Adapter, u64)> + '_ {
+        self.adapters.iter().zip(self.hits.iter().copied())
+    }
+
+    /// get the number of read pairs trimmed by the overlap of mates
+    pub fn overlap_hits(&self) -> u64 {
+        self.overlap_hits
+    }
+
+    // check whether two sequences of the same length match within the mismatch budget
+    fn is_match(&self, a: &[u8], b: &[u8]) -> bool {
+        let allowed = self.max_mismatches.min(a.len() / 8);
+        let mut mismatch = 0;
+        for (x, y) in a.iter().zip(b) {
+            if !x.eq_ignore_ascii_case(y) {
+                mismatch += 1;
+                if mismatch > allowed {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// find the leftmost adapter in a sequence, return its index and start position
+    pub fn find(&self, seq: &[u8]) -> Option<(usize, usize)> {
+        let mut found: Option<(usize, usize)> = None;
+        for (idx, adapter) in self.adapters.iter().enumerate() {
+            let adapter = adapter.seq.as_bytes();
+            let end = found.map_or(seq.len(), |(_, pos)| pos);
+            for pos in 0..end {
+                let overlap = adapter.len().min(seq.len() - pos);
+                if overlap < self.min_overlap {
+                    break;
+                }
+                if self.is_match(&seq[pos..pos + overlap], &adapter[..overlap]) {
+                    found = Some((idx, pos));
+                    break;
+                }
+            }
+        }
+        found
+    }
+
+    /// trim the adapter of a single-end read
+    pub fn trim(&mut self, mut record: OwnedFastx) -> OwnedFastx {
+        if let Some((idx, pos)) = self.find(record.seq.as_bytes()) {
+            self.hits[idx] += 1;
+            record.trim(0, pos);
+        }
+        record
+    }
+
+    /// detect the insert size of a read pair from the overlap of mates, return `None` if the
+    /// insert is not shorter than both reads or the overlap is shorter than `min_pair_overlap`.
+    pub fn find_insert(&self, seq1: &[u8], seq2: &[u8]) -> Option<usize> {
+        let rc2 = revcomp(seq2);
+        let max_insert = seq1.len().min(seq2.len());
+        // prefer the longest insert, i.e. the least trimming
+        (self.min_pair_overlap..max_insert)
+            .rev()
+            .find(|&len| self.is_match(&seq1[..len], &rc2[rc2.len() - len..]))
+    }
+
+    /// trim the adapters of a read pair, using the overlap of mates
+    /// first and falling back to the known adapters of each read.
+    pub fn trim_pair(
+        &mut self,
+        mut record1: OwnedFastx,
+        mut record2: OwnedFastx,
+    ) -> (OwnedFastx, OwnedFastx) {
+        match self.find_insert(record1.seq.as_bytes(), record2.seq.as_bytes()) {
+            Some(insert) => {
+                self.overlap_hits += 1;
+                record1.trim(0, insert);
+                record2.trim(0, insert);
+                (record1, record2)
+            }
+            None => (self.trim(record1), self.trim(record2)),
+        }
+    }
+}
+
+impl Transform for AdapterTrimmer {
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        Some(self.trim(record))
+    }
+}
+
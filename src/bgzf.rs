@@ -0,0 +1,191 @@
+//! Reading of BGZF (blocked gzip) files with virtual offsets
+//!
+//! A BGZF file is a series of gzip members (blocks) of at most 64 KB, as written by
+//! `bgzip`. A position in a BGZF file is a virtual offset, i.e. the offset of a block in
+//! the compressed file shifted left by 16 bits, plus the offset in the uncompressed block.
+//!
+//! ```no_run
+//! use kseq::bgzf::BgzfReader;
+//! use std::io::BufRead;
+//!
+//! let mut reader = BgzfReader::new(std::fs::File::open("reads.fq.gz").unwrap());
+//! let mut line = String::new();
+//! reader.read_line(&mut line).unwrap();
+//! let offset = reader.virtual_offset();
+//! reader.seek_virtual(offset).unwrap();
+//! ```
+
+use flate2::{Crc, Decompress, FlushDecompress};
+use std::cell::Cell;
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+use std::rc::Rc;
+
+// the maximum size of an uncompressed block
+const MAX_BLOCK_SIZE: usize = 65536;
+
+/// check whether the start of a file is the header of a BGZF block
+pub fn is_bgzf(header: &[u8]) -> bool {
+    header.len() >= 16
+        && header[..4] == [0x1f, 0x8b, 8, 4]
+        && header[12..14] == *b"BC"
+        && header[14..16] == [2, 0]
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(msg: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// read into a buffer until it is full or EOF is reached, return the number of bytes read
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(i) => n += i,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
+
+/// a buffered reader of BGZF files, which decompresses a block at a time
+pub struct BgzfReader<R> {
+    inner: R,
+    decompress: Decompress,
+    compressed: Vec<u8>,
+    block: Vec<u8>,
+    pos: usize,
+    // the offsets of the current and the next block in the compressed file
+    block_offset: u64,
+    next_offset: u64,
+    // the virtual offset shared with a Reader
+    shared: Rc<Cell<u64>>,
+}
+
+impl<R: Read> BgzfReader<R> {
+    /// create a new BgzfReader at the start of a BGZF file
+    pub fn new(inner: R) -> Self {
+        BgzfReader {
+            inner,
+            decompress: Decompress::new(false),
+            compressed: Vec::new(),
+            block: Vec::new(),
+            pos: 0,
+            block_offset: 0,
+            next_offset: 0,
+            shared: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// get the virtual offset of the current position
+    pub fn virtual_offset(&self) -> u64 {
+        if self.pos < self.block.len() {
+            self.block_offset << 16 | self.pos as u64
+        } else {
+            // the end of a block is the start of the next block
+            self.next_offset << 16
+        }
+    }
+
+    // get a handle of the virtual offset, which is updated while reading
+    pub(crate) fn shared_offset(&self) -> Rc<Cell<u64>> {
+        Rc::clone(&self.shared)
+    }
+
+    // read and decompress the next block, return false at EOF
+    fn read_block(&mut self) -> io::Result<bool> {
+        self.block.clear();
+        self.pos = 0;
+        self.block_offset = self.next_offset;
+        let mut header = [0u8; 12];
+        match read_full(&mut self.inner, &mut header)? {
+            0 => return Ok(false),
+            12 if header[..4] == [0x1f, 0x8b, 8, 4] => {}
+            _ => return Err(invalid("invalid BGZF block header")),
+        }
+        let mut extra = vec![0u8; u16::from_le_bytes([header[10], header[11]]) as usize];
+        self.inner.read_exact(&mut extra)?;
+        // find the BC subfield of the block size
+        let mut block_size = None;
+        let mut i = 0;
+        while i + 4 <= extra.len() {
+            let len = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+            if extra[i..i + 2] == *b"BC" && len == 2 && i + 6 <= extra.len() {
+                block_size = Some(u16::from_le_bytes([extra[i + 4], extra[i + 5]]) as usize + 1);
+            }
+            i += 4 + len;
+        }
+        let block_size = block_size.ok_or_else(|| invalid("missing BGZF block size"))?;
+        let rest = block_size
+            .checked_sub(header.len() + extra.len() + 8)
+            .ok_or_else(|| invalid("invalid BGZF block size"))?;
+        self.compressed.resize(rest + 8, 0);
+        self.inner.read_exact(&mut self.compressed)?;
+        let footer = &self.compressed[rest..];
+        let crc = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+        let size = u32::from_le_bytes([footer[4], footer[5], footer[6], footer[7]]) as usize;
+        if size > MAX_BLOCK_SIZE {
+            return Err(invalid("invalid BGZF block size"));
+        }
+        self.block.resize(size, 0);
+        self.decompress.reset(false);
+        self.decompress
+            .decompress(
+                &self.compressed[..rest],
+                &mut self.block,
+                FlushDecompress::Finish,
+            )
+            .map_err(invalid)?;
+        let mut checksum = Crc::new();
+        checksum.update(&self.block);
+        if self.decompress.total_out() != size as u64 || checksum.sum() != crc {
+            return Err(invalid("invalid BGZF block data"));
+        }
+        self.next_offset += block_size as u64;
+        Ok(true)
+    }
+}
+
+impl<R: Read + Seek> BgzfReader<R> {
+    /// seek to a virtual offset
+    pub fn seek_virtual(&mut self, offset: u64) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(offset >> 16))?;
+        self.next_offset = offset >> 16;
+        let pos = (offset & 0xffff) as usize;
+        self.read_block()?;
+        if pos > self.block.len() {
+            return Err(invalid(format!("invalid BGZF virtual offset {}", offset)));
+        }
+        self.pos = pos;
+        self.shared.set(self.virtual_offset());
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BgzfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BgzfReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // skip empty blocks, such as the EOF block
+        while self.pos == self.block.len() {
+            if !self.read_block()? {
+                break;
+            }
+        }
+        Ok(&self.block[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.block.len());
+        self.shared.set(self.virtual_offset());
+    }
+}
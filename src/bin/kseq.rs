@@ -0,0 +1,416 @@
+//! a seqkit-like command-line tool built on kseq
+
+use clap::{Args, Parser, Subcommand};
+use kseq::{
+    filter::{Filter, IdSet, Motif, Regex},
+    index::{FastqIndex, IndexedReader},
+    parse_path,
+    record::{Fastx, OwnedFastx},
+    region::Regions,
+    split::{SplitBy, Splitter},
+    stats::{Stats, Summary},
+    writer::create_path,
+};
+use std::{error::Error, io::Write};
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[command(name = "kseq", version, about = "a simple fasta/fastq toolkit")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Io {
+    /// input fastx files or fofn files, `-` for stdin
+    #[arg(default_value = "-")]
+    inputs: Vec<String>,
+    /// output file, `-` for stdout, gzip compressed if it ends with `.gz`
+    #[arg(short, long, default_value = "-")]
+    output: String,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// print simple statistics of each input
+    Stats {
+        /// input fastx files or fofn files, `-` for stdin
+        #[arg(default_value = "-")]
+        inputs: Vec<String>,
+    },
+    /// convert fastq to fasta
+    Fq2fa {
+        #[command(flatten)]
+        io: Io,
+    },
+    /// print the first records
+    Head {
+        /// the number of records
+        #[arg(short, long, default_value_t = 10)]
+        number: usize,
+        #[command(flatten)]
+        io: Io,
+    },
+    /// search records by ID or sequence
+    Grep {
+        /// an ID to search, can be repeated
+        #[arg(short, long)]
+        pattern: Vec<String>,
+        /// a file of IDs to search, one per line, only the first word of a line is used
+        #[arg(short = 'f', long)]
+        pattern_file: Option<String>,
+        /// search patterns as sequence motifs (IUPAC codes allowed) on both strands instead of IDs
+        #[arg(short = 's', long)]
+        by_seq: bool,
+        /// search patterns as regexes of IDs instead of exact IDs
+        #[arg(short = 'r', long, conflicts_with = "by_seq")]
+        use_regex: bool,
+        /// the maximum number of mismatches of sequence motifs
+        #[arg(short, long, default_value_t = 0)]
+        mismatches: usize,
+        /// print the records not matching
+        #[arg(short = 'v', long)]
+        invert: bool,
+        #[command(flatten)]
+        io: Io,
+    },
+    /// get sub-sequences by a 1-based region, e.g. `1:12`, `:12` or `100:`,
+    /// or by the regions of a BED or GFF file
+    #[command(group(clap::ArgGroup::new("regions").required(true).args(["region", "bed", "gff"])))]
+    Subseq {
+        /// the region
+        #[arg(short, long)]
+        region: Option<String>,
+        /// a BED file of regions, sub-sequences are written as fasta
+        #[arg(long)]
+        bed: Option<String>,
+        /// a GFF file of regions, sub-sequences are written as fasta
+        #[arg(long)]
+        gff: Option<String>,
+        /// only use the GFF features of a type, e.g. `gene`
+        #[arg(short = 't', long, requires = "gff")]
+        feature_type: Option<String>,
+        #[command(flatten)]
+        io: Io,
+    },
+    /// index a plain or bgzipped file to `{input}.fqi`, or fetch records by name with the
+    /// index, which is built if it doesn't exist
+    Fqidx {
+        /// the input fastx file
+        input: String,
+        /// the names of records to fetch
+        names: Vec<String>,
+        /// output file, `-` for stdout, gzip compressed if it ends with `.gz`
+        #[arg(short, long, default_value = "-")]
+        output: String,
+    },
+    /// transform sequences
+    Seq {
+        /// reverse complement sequences
+        #[arg(short, long)]
+        revcomp: bool,
+        /// convert sequences to upper case
+        #[arg(short, long)]
+        upper: bool,
+        /// the minimum sequence length
+        #[arg(short, long, default_value_t = 0)]
+        min_len: usize,
+        #[command(flatten)]
+        io: Io,
+    },
+    /// rename IDs to a prefix and a serial number
+    Rename {
+        /// the prefix of new IDs
+        #[arg(short, long, default_value = "")]
+        prefix: String,
+        /// drop the descriptions
+        #[arg(short = 'D', long)]
+        drop_des: bool,
+        #[command(flatten)]
+        io: Io,
+    },
+    /// split records into files of a number of records or bases, into a number of parts,
+    /// or by a tag in descriptions
+    #[command(group(clap::ArgGroup::new("by").required(true).args(["size", "parts", "bases", "tag"])))]
+    Split {
+        /// the number of records of each part
+        #[arg(short, long)]
+        size: Option<usize>,
+        /// the number of parts, records are distributed in turn
+        #[arg(short = 'n', long)]
+        parts: Option<usize>,
+        /// the minimum number of bases of each part
+        #[arg(short, long)]
+        bases: Option<usize>,
+        /// split by the value of a tag in descriptions, e.g. `sample=`, `{prefix}.{value}.fq`
+        #[arg(short, long)]
+        tag: Option<String>,
+        /// the prefix of output files, `{prefix}.part_001.fq`
+        #[arg(short, long, default_value = "split")]
+        prefix: String,
+        /// gzip compress output files
+        #[arg(short, long)]
+        gzip: bool,
+        /// input fastx files or fofn files, `-` for stdin
+        #[arg(default_value = "-")]
+        inputs: Vec<String>,
+    },
+}
+
+// apply a function to each record of inputs, stop if it returns false
+fn each_record<F: FnMut(&Fastx) -> Result<bool>>(inputs: &[String], mut f: F) -> Result<()> {
+    for input in inputs {
+        let mut records = parse_path(input).map_err(|e| format!("{}: {}", input, e))?;
+        while let Some(record) = records.iter_record()? {
+            if !f(&record)? {
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+// parse a 1-based inclusive region to a 0-based half-open range
+fn parse_region(region: &str) -> Result<(usize, usize)> {
+    let invalid = || format!("invalid region: {}", region);
+    let (start, end) = region.split_once(':').ok_or_else(invalid)?;
+    let start = match start {
+        "" => 1,
+        s => s.parse::<usize>().map_err(|_| invalid())?,
+    };
+    let end = match end {
+        "" => usize::MAX,
+        s => s.parse::<usize>().map_err(|_| invalid())?,
+    };
+    if start == 0 || start > end {
+        return Err(invalid().into());
+    }
+    Ok((start - 1, end))
+}
+
+fn stats(inputs: &[String]) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    writeln!(out, "file\t{}", Summary::tsv_header())?;
+    for input in inputs {
+        let mut records = parse_path(input).map_err(|e| format!("{}: {}", input, e))?;
+        let stats = Stats::from_paths(&mut records)?;
+        writeln!(out, "{}\t{}", input, stats.summary())?;
+    }
+    Ok(())
+}
+
+fn split(inputs: &[String], by: SplitBy, prefix: &str, gzip: bool) -> Result<()> {
+    if matches!(
+        by,
+        SplitBy::Records(0) | SplitBy::Parts(0) | SplitBy::Bases(0)
+    ) {
+        return Err("the size of a part must be larger than 0".into());
+    }
+    let shard = match by {
+        SplitBy::Key(_) => "{key}",
+        _ => "part_{part}",
+    };
+    let gz = if gzip { ".gz" } else { "" };
+    let mut splitter = Splitter::new(by, &format!("{}.{}.{{ext}}{}", prefix, shard, gz));
+    each_record(inputs, |record| {
+        splitter.write(record)?;
+        Ok(true)
+    })?;
+    splitter.finish()?;
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Stats { inputs } => stats(&inputs),
+        Command::Fq2fa { io } => {
+            let mut out = create_path(&io.output)?;
+            each_record(&io.inputs, |record| {
+                out.write_fasta(record)?;
+                Ok(true)
+            })?;
+            Ok(out.finish()?)
+        }
+        Command::Head { number, io } => {
+            let mut out = create_path(&io.output)?;
+            let mut n = 0;
+            if number > 0 {
+                each_record(&io.inputs, |record| {
+                    out.write_record(record)?;
+                    n += 1;
+                    Ok(n < number)
+                })?;
+            }
+            Ok(out.finish()?)
+        }
+        Command::Grep {
+            pattern,
+            pattern_file,
+            by_seq,
+            use_regex,
+            mismatches,
+            invert,
+            io,
+        } => {
+            let mut patterns = match pattern_file {
+                Some(file) => IdSet::from_path(&file).map_err(|e| format!("{}: {}", file, e))?,
+                None => IdSet::new(),
+            };
+            pattern.iter().for_each(|p| patterns.insert(p));
+            let filters = if by_seq {
+                patterns
+                    .iter()
+                    .map(|p| Ok(Filter::Motif(Motif::new(p, mismatches)?)))
+                    .collect::<Result<Vec<_>>>()?
+            } else if use_regex {
+                patterns
+                    .iter()
+                    .map(|p| Ok(Filter::Head(Regex::new(p)?)))
+                    .collect::<Result<Vec<_>>>()?
+            } else {
+                vec![Filter::Ids(patterns)]
+            };
+            let mut out = create_path(&io.output)?;
+            each_record(&io.inputs, |record| {
+                let matched = filters.iter().any(|f| f.is_match(record));
+                if matched != invert {
+                    out.write_record(record)?;
+                }
+                Ok(true)
+            })?;
+            Ok(out.finish()?)
+        }
+        Command::Subseq {
+            region,
+            bed,
+            gff,
+            feature_type,
+            io,
+        } => {
+            let regions = match (bed, gff) {
+                (Some(bed), _) => {
+                    Some(Regions::from_bed(&bed).map_err(|e| format!("{}: {}", bed, e))?)
+                }
+                (_, Some(gff)) => Some(
+                    Regions::from_gff(&gff, feature_type.as_deref())
+                        .map_err(|e| format!("{}: {}", gff, e))?,
+                ),
+                _ => None,
+            };
+            let mut out = create_path(&io.output)?;
+            match regions {
+                Some(regions) => each_record(&io.inputs, |record| {
+                    for subseq in regions.extract(record) {
+                        out.write_fasta(&subseq)?;
+                    }
+                    Ok(true)
+                })?,
+                None => {
+                    // exactly one of them is given, as required by clap
+                    let (start, end) = parse_region(&region.unwrap())?;
+                    each_record(&io.inputs, |record| {
+                        let mut record = record.to_owned_fastx();
+                        record.trim(start, end);
+                        out.write_record(&record)?;
+                        Ok(true)
+                    })?
+                }
+            }
+            Ok(out.finish()?)
+        }
+        Command::Fqidx {
+            input,
+            names,
+            output,
+        } => {
+            if names.is_empty() {
+                let index = FastqIndex::build(&input).map_err(|e| format!("{}: {}", input, e))?;
+                return Ok(index.save(FastqIndex::index_path(&input))?);
+            }
+            let mut reader =
+                IndexedReader::open(&input).map_err(|e| format!("{}: {}", input, e))?;
+            let mut out = create_path(&output)?;
+            for name in &names {
+                let record = reader
+                    .fetch(name)?
+                    .ok_or_else(|| format!("{}: no record {}", input, name))?;
+                out.write_record(&record)?;
+            }
+            Ok(out.finish()?)
+        }
+        Command::Seq {
+            revcomp,
+            upper,
+            min_len,
+            io,
+        } => {
+            let mut out = create_path(&io.output)?;
+            each_record(&io.inputs, |record| {
+                if record.len() < min_len {
+                    return Ok(true);
+                }
+                let mut record = record.to_owned_fastx();
+                if revcomp {
+                    record.revcomp();
+                }
+                if upper {
+                    record.unmask();
+                }
+                out.write_record(&record)?;
+                Ok(true)
+            })?;
+            Ok(out.finish()?)
+        }
+        Command::Rename {
+            prefix,
+            drop_des,
+            io,
+        } => {
+            let mut out = create_path(&io.output)?;
+            let mut n = 0;
+            each_record(&io.inputs, |record| {
+                n += 1;
+                let record = OwnedFastx {
+                    head: format!("{}{}", prefix, n),
+                    des: if drop_des {
+                        String::new()
+                    } else {
+                        record.des().to_string()
+                    },
+                    ..record.to_owned_fastx()
+                };
+                out.write_record(&record)?;
+                Ok(true)
+            })?;
+            Ok(out.finish()?)
+        }
+        Command::Split {
+            size,
+            parts,
+            bases,
+            tag,
+            prefix,
+            gzip,
+            inputs,
+        } => {
+            // exactly one of them is given, as required by clap
+            let by = match (size, parts, bases, tag) {
+                (Some(size), ..) => SplitBy::Records(size),
+                (_, Some(parts), ..) => SplitBy::Parts(parts),
+                (_, _, Some(bases), _) => SplitBy::Bases(bases),
+                (.., Some(tag)) => SplitBy::des_tag(&tag),
+                _ => unreachable!(),
+            };
+            split(&inputs, by, &prefix, gzip)
+        }
+    }
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
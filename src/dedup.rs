@@ -0,0 +1,204 @@
+//! Detection and removal of duplicated reads
+//!
+//! Reads (or pairs of reads) with identical sequences are duplicates, only the first
+//! occurrence is kept. Sequences are stored as 64-bit hashes, so the exact mode needs
+//! 8 bytes per distinct sequence (plus the overhead of a `HashSet`). The approximate mode
+//! uses a Bloom filter of a fixed size, which may mark a few unique reads as duplicates.
+//!
+//! ```
+//! use kseq::dedup::Dedup;
+//!
+//! let mut records = kseq::parse_reader(&b">1\nACGT\n>2\nACGT\n>3\nTTTT\n"[..]).unwrap();
+//! let mut dedup = Dedup::exact();
+//! let mut writer = kseq::writer::Writer::new(Vec::new());
+//! assert_eq!(dedup.run(&mut records, &mut writer).unwrap(), 2);
+//! assert_eq!(dedup.num_duplicates(), 1);
+//! ```
+
+use crate::record::{FastxRecord, Result as ParseResult};
+use crate::writer::Writer;
+use crate::{each_pair, Paths};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+/// a Bloom filter of 64-bit hashes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// create a Bloom filter for an expected number of items with a false positive rate,
+    /// return an error if the rate is not in `(0, 1)`. The filter has at least 64 bits.
+    pub fn new(expected: usize, fp_rate: f64) -> io::Result<Self> {
+        if !(fp_rate > 0.0 && fp_rate < 1.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the false positive rate must be in (0, 1): {}", fp_rate),
+            ));
+        }
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-(expected.max(1) as f64) * fp_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let num_bits = num_bits.max(64);
+        let num_hashes = ((num_bits as f64 / expected.max(1) as f64) * ln2).round() as u32;
+        Ok(BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_hashes: num_hashes.max(1),
+        })
+    }
+
+    /// get the size of the filter in bytes
+    pub fn size(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    /// insert a hash, return true if it may have been inserted before
+    pub fn insert(&mut self, hash: u64) -> bool {
+        let num_bits = self.bits.len() as u64 * 64;
+        // double hashing, the second hash is odd to visit distinct bits
+        let h2 = mix(hash) | 1;
+        let mut found = true;
+        for i in 0..self.num_hashes as u64 {
+            let bit = hash.wrapping_add(i.wrapping_mul(h2)) % num_bits;
+            let (word, mask) = ((bit / 64) as usize, 1u64 << (bit % 64));
+            found &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        found
+    }
+}
+
+// the finalizer of splitmix64
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn hash_seqs(seqs: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for seq in seqs {
+        hasher.write(seq.as_bytes());
+        // separate the mates of a pair
+        hasher.write_u8(0);
+    }
+    hasher.finish()
+}
+
+#[derive(Debug, Clone)]
+enum Seen {
+    Exact(HashSet<u64>),
+    Bloom(BloomFilter),
+}
+
+/// a detector of duplicated reads, which also counts them
+#[derive(Debug, Clone)]
+pub struct Dedup {
+    seen: Seen,
+    num_records: u64,
+    num_duplicates: u64,
+}
+
+impl Dedup {
+    /// create a Dedup storing the hashes of all distinct sequences
+    pub fn exact() -> Self {
+        Self::with(Seen::Exact(HashSet::new()))
+    }
+
+    /// create a Dedup of bounded memory with a Bloom filter for an expected number of
+    /// distinct sequences, a unique read is taken as a duplicate with a rate of about `fp_rate`.
+    pub fn approximate(expected: usize, fp_rate: f64) -> io::Result<Self> {
+        let bloom = BloomFilter::new(expected, fp_rate)?;
+        Ok(Self::with(Seen::Bloom(bloom)))
+    }
+
+    fn with(seen: Seen) -> Self {
+        Dedup {
+            seen,
+            num_records: 0,
+            num_duplicates: 0,
+        }
+    }
+
+    fn check(&mut self, hash: u64) -> bool {
+        let duplicate = match &mut self.seen {
+            Seen::Exact(set) => !set.insert(hash),
+            Seen::Bloom(bloom) => bloom.insert(hash),
+        };
+        self.num_records += 1;
+        if duplicate {
+            self.num_duplicates += 1;
+        }
+        duplicate
+    }
+
+    /// check whether the sequence of a record was seen before, and remember it
+    pub fn is_duplicate<R: FastxRecord>(&mut self, record: &R) -> bool {
+        self.check(hash_seqs(&[record.seq()]))
+    }
+
+    /// check whether the sequences of a pair of records were seen before, and remember them.
+    /// A pair is a duplicate only if both mates are the same as a previous pair.
+    pub fn is_duplicate_pair<R: FastxRecord>(&mut self, r1: &R, r2: &R) -> bool {
+        self.check(hash_seqs(&[r1.seq(), r2.seq()]))
+    }
+
+    /// get the number of checked records (or pairs)
+    pub fn num_records(&self) -> u64 {
+        self.num_records
+    }
+
+    /// get the number of duplicated records (or pairs)
+    pub fn num_duplicates(&self) -> u64 {
+        self.num_duplicates
+    }
+
+    /// get the fraction of duplicated records (or pairs), 0 if there is no record
+    pub fn duplication_rate(&self) -> f64 {
+        if self.num_records == 0 {
+            0.0
+        } else {
+            self.num_duplicates as f64 / self.num_records as f64
+        }
+    }
+
+    /// write the first occurrences of the records of a Reader or Readers to a Writer,
+    /// return the number of records written.
+    pub fn run<W: Write>(&mut self, paths: &mut Paths, writer: &mut Writer<W>) -> ParseResult<u64> {
+        let mut n = 0;
+        while let Some(record) = paths.iter_record()? {
+            if !self.is_duplicate(&record) {
+                writer.write_record(&record)?;
+                n += 1;
+            }
+        }
+        writer.flush()?;
+        Ok(n)
+    }
+
+    /// write the first occurrences of the pairs of records of two paired Readers to two
+    /// Writers, return the number of pairs written.
+    pub fn run_paired<W1: Write, W2: Write>(
+        &mut self,
+        paths1: &mut Paths,
+        paths2: &mut Paths,
+        writer1: &mut Writer<W1>,
+        writer2: &mut Writer<W2>,
+    ) -> ParseResult<u64> {
+        let mut n = 0;
+        each_pair(paths1, paths2, |r1, r2| {
+            if !self.is_duplicate_pair(r1, r2) {
+                writer1.write_record(r1)?;
+                writer2.write_record(r2)?;
+                n += 1;
+            }
+            Ok(())
+        })?;
+        writer1.flush()?;
+        writer2.flush()?;
+        Ok(n)
+    }
+}
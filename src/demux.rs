@@ -0,0 +1,318 @@
+//! Barcode demultiplexing of records
+//!
+//! Barcodes are read from a sample sheet and searched at a configurable location of each
+//! record, a barcode matches a sample with up to `max_mismatches` mismatches if no other
+//! sample is as close. Barcodes of samples which could be confused with each other under
+//! `max_mismatches` mismatches are rejected. Dual-index barcodes such as `ACGT+TTGG` are
+//! compared without the `+`.
+//!
+//! ```
+//! use kseq::demux::{BarcodeLocation, Demultiplexer, SampleSheet};
+//!
+//! let mut sheet = SampleSheet::new();
+//! sheet.add("s1", "ACGTAC");
+//! sheet.add("s2", "TTGGCA");
+//! let demux = Demultiplexer::new(&sheet, BarcodeLocation::Header, 1).unwrap();
+//! assert_eq!(demux.assign(b"ACGTAG"), Some("s1"));
+//! assert_eq!(demux.assign(b"AAAAAA"), None);
+//! ```
+
+use crate::record::{Fastx, Result as ParseResult};
+use crate::writer::{create_path, Output, Writer};
+use crate::Paths;
+use std::collections::{hash_map::Entry, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// the name of the bucket of records without a matched barcode
+pub const UNDETERMINED: &str = "undetermined";
+
+/// the location of barcodes in records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeLocation {
+    /// the index field of an Illumina header, i.e. the last `:`-separated field of `des()`,
+    /// e.g. `ACGTAC+TTGGCA` of `@r1 1:N:0:ACGTAC+TTGGCA`
+    Header,
+    /// the start of reads, the barcode is removed from reads if `trim` is true
+    ReadStart {
+        /// remove the barcode from reads
+        trim: bool,
+    },
+    /// the start of the records of a separate index read file
+    IndexRead,
+}
+
+/// a sample sheet of sample names and barcodes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SampleSheet {
+    samples: Vec<(String, String)>,
+}
+
+impl SampleSheet {
+    /// create an empty SampleSheet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// load a sample sheet from a file, one `sample barcode` per line separated by a tab,
+    /// a comma or spaces, empty lines and lines starting with `#` are skipped, as well as
+    /// a header line whose barcode is not a DNA sequence.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut sheet = SampleSheet::new();
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|f| !f.is_empty());
+            match (fields.next(), fields.next()) {
+                (Some(sample), Some(barcode)) if is_barcode(barcode) => sheet.add(sample, barcode),
+                (Some(_), Some(_)) if sheet.samples.is_empty() => {}
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid sample sheet line {}: {}", i + 1, line),
+                    ))
+                }
+            }
+        }
+        Ok(sheet)
+    }
+
+    /// add a sample and its barcode
+    pub fn add(&mut self, sample: &str, barcode: &str) {
+        self.samples.push((sample.to_string(), barcode.to_string()));
+    }
+
+    /// get the number of samples
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// check whether the sample sheet is empty
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+fn is_barcode(barcode: &str) -> bool {
+    barcode.bytes().all(|b| b"ACGTNacgtn+-".contains(&b))
+}
+
+// upper case a barcode and remove the separator of dual-index barcodes
+fn normalize(barcode: &[u8]) -> Vec<u8> {
+    barcode
+        .iter()
+        .filter(|&&b| b != b'+' && b != b'-')
+        .map(u8::to_ascii_uppercase)
+        .collect()
+}
+
+// the number of mismatches of two barcodes of the same length, an `N` is always a mismatch
+fn mismatches(a: &[u8], b: &[u8]) -> usize {
+    a.iter()
+        .zip(b)
+        .filter(|(&x, &y)| x != y || x == b'N')
+        .count()
+}
+
+/// a demultiplexer of records by barcodes
+#[derive(Debug, Clone)]
+pub struct Demultiplexer {
+    names: Vec<String>,
+    barcodes: Vec<Vec<u8>>,
+    exact: HashMap<Vec<u8>, usize>,
+    location: BarcodeLocation,
+    max_mismatches: usize,
+    // the counts of samples, the last one is for undetermined records
+    counts: Vec<u64>,
+}
+
+impl Demultiplexer {
+    /// create a new Demultiplexer, return an error if the sample sheet is empty, a sample name
+    /// is duplicated, [`UNDETERMINED`] or not a valid file name, a barcode contains `N`, the
+    /// barcodes have different lengths, or two barcodes collide under `max_mismatches`
+    /// mismatches.
+    pub fn new(
+        sheet: &SampleSheet,
+        location: BarcodeLocation,
+        max_mismatches: usize,
+    ) -> io::Result<Self> {
+        let invalid = |msg: String| Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        if sheet.is_empty() {
+            return invalid("the sample sheet is empty".to_string());
+        }
+        let names: Vec<String> = sheet.samples.iter().map(|(s, _)| s.clone()).collect();
+        let barcodes: Vec<Vec<u8>> = sheet
+            .samples
+            .iter()
+            .map(|(_, b)| normalize(b.as_bytes()))
+            .collect();
+        let mut exact = HashMap::new();
+        for (i, barcode) in barcodes.iter().enumerate() {
+            let name = &names[i];
+            // sample names are used in output paths
+            if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\', '\0']) {
+                return invalid(format!("invalid sample name: {:?}", name));
+            }
+            if name.eq_ignore_ascii_case(UNDETERMINED) {
+                return invalid(format!("reserved sample name: {}", name));
+            }
+            if names[..i].contains(name) {
+                return invalid(format!("duplicated sample name: {}", name));
+            }
+            if barcode.contains(&b'N') {
+                return invalid(format!("the barcode of {} contains N", name));
+            }
+            if barcode.len() != barcodes[0].len() {
+                return invalid(format!(
+                    "the barcode of {} has a different length from the barcode of {}",
+                    names[i], names[0]
+                ));
+            }
+            for j in 0..i {
+                if mismatches(barcode, &barcodes[j]) <= max_mismatches * 2 {
+                    return invalid(format!(
+                        "the barcodes of {} and {} collide with {} mismatches",
+                        names[j], names[i], max_mismatches
+                    ));
+                }
+            }
+            exact.insert(barcode.clone(), i);
+        }
+        Ok(Demultiplexer {
+            counts: vec![0; names.len() + 1],
+            names,
+            barcodes,
+            exact,
+            location,
+            max_mismatches,
+        })
+    }
+
+    /// get the length of barcodes
+    pub fn barcode_len(&self) -> usize {
+        self.barcodes[0].len()
+    }
+
+    // get the index of the sample of a barcode
+    fn find(&self, barcode: &[u8]) -> Option<usize> {
+        let barcode = normalize(barcode);
+        if barcode.len() != self.barcode_len() {
+            return None;
+        }
+        if let Some(&i) = self.exact.get(&barcode) {
+            return Some(i);
+        }
+        // barcodes do not collide, so at most one barcode is within max_mismatches
+        self.barcodes
+            .iter()
+            .position(|b| mismatches(&barcode, b) <= self.max_mismatches)
+    }
+
+    /// get the sample of a barcode, `None` if no sample matches
+    pub fn assign(&self, barcode: &[u8]) -> Option<&str> {
+        self.find(barcode).map(|i| self.names[i].as_str())
+    }
+
+    // get the barcode of a record
+    fn barcode<'a>(&self, record: &'a Fastx, index: Option<&'a Fastx>) -> &'a [u8] {
+        let len = self.barcode_len();
+        let prefix = |seq: &'a str| &seq.as_bytes()[..len.min(seq.len())];
+        match self.location {
+            BarcodeLocation::Header => record
+                .des()
+                .rsplit(':')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .as_bytes(),
+            BarcodeLocation::ReadStart { .. } => prefix(record.seq()),
+            BarcodeLocation::IndexRead => index.map_or(&[], |r| prefix(r.seq())),
+        }
+    }
+
+    /// get the sample of a record, `index` is the record of the index read file for
+    /// `BarcodeLocation::IndexRead`. Return `None` if no sample matches.
+    pub fn assign_record(&self, record: &Fastx, index: Option<&Fastx>) -> Option<&str> {
+        self.assign(self.barcode(record, index))
+    }
+
+    /// demultiplex all records of a Reader or Readers into per-sample files, `index` is the
+    /// Reader of the index read file for `BarcodeLocation::IndexRead`. The output path of
+    /// a sample is `template` with `{sample}` replaced by the sample name, or
+    /// [`UNDETERMINED`] for records without a matched barcode.
+    pub fn run(
+        &mut self,
+        paths: &mut Paths,
+        mut index: Option<&mut Paths>,
+        template: &str,
+    ) -> ParseResult<()> {
+        if !template.contains("{sample}") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("the template {} must contain {{sample}}", template),
+            )
+            .into());
+        }
+        if self.location == BarcodeLocation::IndexRead && index.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "missing index reads").into());
+        }
+        let mut writers: HashMap<usize, Writer<Output>> = HashMap::new();
+        while let Some(record) = paths.iter_record()? {
+            let index_record = match index.as_mut() {
+                Some(index) => Some(index.iter_record()?.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the index read file has fewer records",
+                    )
+                })?),
+                None => None,
+            };
+            let sample = self.find(self.barcode(&record, index_record.as_ref()));
+            let slot = sample.unwrap_or(self.names.len());
+            self.counts[slot] += 1;
+            let writer = match writers.entry(slot) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let name = sample.map_or(UNDETERMINED, |i| self.names[i].as_str());
+                    entry.insert(create_path(template.replace("{sample}", name))?)
+                }
+            };
+            match self.location {
+                BarcodeLocation::ReadStart { trim: true } if sample.is_some() => {
+                    let mut record = record.to_owned_fastx();
+                    record.trim(self.barcode_len(), usize::MAX);
+                    writer.write_record(&record)?;
+                }
+                _ => writer.write_record(&record)?,
+            }
+        }
+        if let Some(index) = index {
+            if index.iter_record()?.is_some() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the index read file has more records",
+                )
+                .into());
+            }
+        }
+        for (_, writer) in writers.drain() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    /// get the numbers of records of samples, followed by the number of [`UNDETERMINED`] records
+    pub fn counts(&self) -> Vec<(&str, u64)> {
+        self.names
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(UNDETERMINED))
+            .zip(self.counts.iter().copied())
+            .collect()
+    }
+}
@@ -0,0 +1,334 @@
+//! Record filters by IDs, header regexes or sequence motifs
+//!
+//! Regex filters require the `regex` feature.
+//!
+//! ```
+//! use kseq::filter::{Filter, Motif};
+//!
+//! let mut records = kseq::parse_reader(&b">r1\nACGTTGCA\n>r2\nTTTTTTTT\n"[..]).unwrap();
+//! let filter = Filter::Motif(Motif::new("TGCR", 0).unwrap());
+//! let hits = filter.grep(&mut records).unwrap();
+//! assert_eq!(hits.len(), 1);
+//! assert_eq!(hits[0].0.head, "r1");
+//! assert_eq!((hits[0].1[0].start, hits[0].1[0].end), (4, 8));
+//! ```
+
+use crate::record::{FastxRecord, OwnedFastx, Result as ParseResult};
+use crate::seq::revcomp;
+use crate::writer::Writer;
+use crate::Paths;
+#[cfg(feature = "regex")]
+pub use regex::Regex;
+use std::collections::HashSet;
+use std::iter::FromIterator;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// the strand of a match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strand {
+    /// the forward strand, also used by the matches of IDs and headers
+    Forward,
+    /// the reverse complement strand
+    Reverse,
+}
+
+/// a match in a record, the coordinates are 0-based and half-open in the matched field,
+/// i.e. `head()` for IDs and head regexes, `des()` for description regexes, and
+/// the forward strand of `seq()` for motifs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// the start of the match
+    pub start: usize,
+    /// the end of the match
+    pub end: usize,
+    /// the strand of the match
+    pub strand: Strand,
+    /// the number of mismatches, always 0 except for motifs
+    pub mismatches: usize,
+}
+
+impl Match {
+    fn text(start: usize, end: usize) -> Self {
+        Match {
+            start,
+            end,
+            strand: Strand::Forward,
+            mismatches: 0,
+        }
+    }
+}
+
+/// a set of record IDs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdSet {
+    ids: HashSet<String>,
+}
+
+impl IdSet {
+    /// create an empty IdSet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// load IDs from a file, one ID per line, only the first word of a line is used
+    /// and a leading `>` or `@` is removed, empty lines and lines starting with `#` are skipped.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut ids = IdSet::new();
+        for line in fs::read_to_string(path)?.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let id = line.trim_start_matches(['>', '@']);
+            if let Some(id) = id.split_whitespace().next() {
+                ids.insert(id);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// add an ID
+    pub fn insert(&mut self, id: &str) {
+        self.ids.insert(id.to_string());
+    }
+
+    /// check whether an ID is in this set
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+
+    /// get the number of IDs
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// check whether this set is empty
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// iterate over the IDs, in an arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.ids.iter().map(String::as_str)
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for IdSet {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        IdSet {
+            ids: iter.into_iter().map(|id| id.as_ref().to_string()).collect(),
+        }
+    }
+}
+
+// bit masks of IUPAC codes, A=1, C=2, G=4, T/U=8, 0 for other bytes
+static IUPAC: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let codes: [(u8, u8); 16] = [
+        (b'A', 1),
+        (b'C', 2),
+        (b'G', 4),
+        (b'T', 8),
+        (b'U', 8),
+        (b'R', 1 | 4),
+        (b'Y', 2 | 8),
+        (b'S', 2 | 4),
+        (b'W', 1 | 8),
+        (b'K', 4 | 8),
+        (b'M', 1 | 2),
+        (b'B', 2 | 4 | 8),
+        (b'D', 1 | 4 | 8),
+        (b'H', 1 | 2 | 8),
+        (b'V', 1 | 2 | 4),
+        (b'N', 15),
+    ];
+    let mut i = 0;
+    while i < codes.len() {
+        let (code, mask) = codes[i];
+        table[code as usize] = mask;
+        table[(code + 32) as usize] = mask;
+        i += 1;
+    }
+    table
+};
+
+/// a sequence motif with IUPAC codes, which is searched with up to `max_mismatches`
+/// mismatches on both strands. A base of a sequence matches a code of the motif if it
+/// is one of the bases of the code, e.g. `A` and `G` match `R`, but `R` only matches
+/// `R`, `V`, `D` and `N`; bytes other than IUPAC codes never match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Motif {
+    forward: Vec<u8>,
+    // None if the motif is its own reverse complement
+    reverse: Option<Vec<u8>>,
+    /// the maximum number of mismatches, default is 0
+    pub max_mismatches: usize,
+    /// also search the reverse complement strand, default is true
+    pub both_strands: bool,
+}
+
+impl Motif {
+    /// create a new Motif, return an error if the motif has a character other than IUPAC codes
+    pub fn new(motif: &str, max_mismatches: usize) -> io::Result<Self> {
+        if let Some(c) = motif
+            .chars()
+            .find(|&c| !c.is_ascii() || IUPAC[c as usize] == 0)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid IUPAC code {:?} in motif {}", c, motif),
+            ));
+        }
+        let encode = |seq: &[u8]| seq.iter().map(|&b| IUPAC[b as usize]).collect::<Vec<_>>();
+        let forward = encode(motif.as_bytes());
+        let reverse = encode(&revcomp(motif.as_bytes()));
+        Ok(Motif {
+            reverse: if reverse == forward {
+                None
+            } else {
+                Some(reverse)
+            },
+            forward,
+            max_mismatches,
+            both_strands: true,
+        })
+    }
+
+    /// get the length of the motif
+    pub fn len(&self) -> usize {
+        self.forward.len()
+    }
+
+    /// check whether the motif is empty
+    pub fn is_empty(&self) -> bool {
+        self.forward.is_empty()
+    }
+
+    /// find all (possibly overlapping) matches in a sequence, sorted by start and strand
+    pub fn find(&self, seq: &[u8]) -> Vec<Match> {
+        let mut matches = Vec::new();
+        if self.is_empty() || seq.len() < self.len() {
+            return matches;
+        }
+        for start in 0..=seq.len() - self.len() {
+            let window = &seq[start..start + self.len()];
+            if let Some(mismatches) = self.mismatches(&self.forward, window) {
+                matches.push(self.new_match(start, Strand::Forward, mismatches));
+            }
+            if let (true, Some(reverse)) = (self.both_strands, &self.reverse) {
+                if let Some(mismatches) = self.mismatches(reverse, window) {
+                    matches.push(self.new_match(start, Strand::Reverse, mismatches));
+                }
+            }
+        }
+        matches
+    }
+
+    // count the mismatches of a window, None if there are more than max_mismatches
+    fn mismatches(&self, motif: &[u8], window: &[u8]) -> Option<usize> {
+        let mut mismatches = 0;
+        for (&code, &b) in motif.iter().zip(window) {
+            let base = IUPAC[b as usize];
+            if base == 0 || base & !code != 0 {
+                mismatches += 1;
+                if mismatches > self.max_mismatches {
+                    return None;
+                }
+            }
+        }
+        Some(mismatches)
+    }
+
+    fn new_match(&self, start: usize, strand: Strand, mismatches: usize) -> Match {
+        Match {
+            start,
+            end: start + self.len(),
+            strand,
+            mismatches,
+        }
+    }
+}
+
+/// a filter of records
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// match IDs, i.e. `head()`, in a set
+    Ids(IdSet),
+    /// match `head()` by a regex
+    #[cfg(feature = "regex")]
+    Head(Regex),
+    /// match `des()` by a regex
+    #[cfg(feature = "regex")]
+    Des(Regex),
+    /// match sequences by a motif
+    Motif(Motif),
+}
+
+impl Filter {
+    /// find the matches in a record, empty if the record does not match
+    pub fn find<R: FastxRecord>(&self, record: &R) -> Vec<Match> {
+        match self {
+            Filter::Ids(ids) if ids.contains(record.head()) => {
+                vec![Match::text(0, record.head().len())]
+            }
+            Filter::Ids(_) => Vec::new(),
+            #[cfg(feature = "regex")]
+            Filter::Head(re) => find_regex(re, record.head()),
+            #[cfg(feature = "regex")]
+            Filter::Des(re) => find_regex(re, record.des()),
+            Filter::Motif(motif) => motif.find(record.seq().as_bytes()),
+        }
+    }
+
+    /// check whether a record matches
+    pub fn is_match<R: FastxRecord>(&self, record: &R) -> bool {
+        match self {
+            Filter::Ids(ids) => ids.contains(record.head()),
+            #[cfg(feature = "regex")]
+            Filter::Head(re) => re.is_match(record.head()),
+            #[cfg(feature = "regex")]
+            Filter::Des(re) => re.is_match(record.des()),
+            Filter::Motif(motif) => !motif.find(record.seq().as_bytes()).is_empty(),
+        }
+    }
+
+    /// get all matching records of a Reader or Readers and their matches
+    pub fn grep(&self, paths: &mut Paths) -> ParseResult<Vec<(OwnedFastx, Vec<Match>)>> {
+        let mut hits = Vec::new();
+        while let Some(record) = paths.iter_record()? {
+            let matches = self.find(&record);
+            if !matches.is_empty() {
+                hits.push((record.to_owned_fastx(), matches));
+            }
+        }
+        Ok(hits)
+    }
+
+    /// write the matching (or not matching if `invert`) records of a Reader or Readers
+    /// to a Writer, return the number of records written.
+    pub fn run<W: Write>(
+        &self,
+        paths: &mut Paths,
+        writer: &mut Writer<W>,
+        invert: bool,
+    ) -> ParseResult<u64> {
+        let mut n = 0;
+        while let Some(record) = paths.iter_record()? {
+            if self.is_match(&record) != invert {
+                writer.write_record(&record)?;
+                n += 1;
+            }
+        }
+        writer.flush()?;
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "regex")]
+fn find_regex(re: &Regex, text: &str) -> Vec<Match> {
+    re.find_iter(text)
+        .map(|m| Match::text(m.start(), m.end()))
+        .collect()
+}
@@ -0,0 +1,259 @@
+//! Random access to records by name, similar to `samtools fqidx`
+//!
+//! A [`FastqIndex`] stores the offset and the length of each record in the uncompressed
+//! stream of a file, which is built from the checkpoints of a Reader. Records of a plain
+//! file are fetched by seeking to their offsets, and records of a bgzipped file are fetched
+//! by seeking to their BGZF virtual offsets, other gzipped files can't be indexed.
+//!
+//! An index is saved as a tab-separated text file, usually `{path}.fqi`, with a line of
+//! `name offset length` for each record, followed by the virtual offset for bgzipped files.
+//!
+//! ```no_run
+//! use kseq::index::IndexedReader;
+//!
+//! let mut reader = IndexedReader::open("reads.fq.gz").unwrap();
+//! let record = reader.fetch("read1").unwrap().unwrap();
+//! println!("{}\n{}", record.seq, record.qual);
+//! ```
+
+use crate::bgzf::{self, BgzfReader};
+use crate::record::{OwnedFastx, ParseError, Result as ParseResult};
+use crate::{parse_path, parse_reader, Paths};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// the position of a record in a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexRecord {
+    /// the name of the record
+    pub name: String,
+    /// the offset of the record in the uncompressed stream
+    pub offset: u64,
+    /// the length of the record in bytes, including newlines
+    pub length: u64,
+    /// the BGZF virtual offset of the record, `None` for plain files
+    pub virtual_offset: Option<u64>,
+}
+
+fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(msg: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn invalid_line(i: usize, line: &str) -> io::Error {
+    invalid(format!("invalid index line {}: {}", i + 1, line))
+}
+
+// the first bytes of a file, to check whether it is gzipped or bgzipped
+fn read_header(file: &mut File) -> io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(16);
+    file.take(16).read_to_end(&mut header)?;
+    Ok(header)
+}
+
+// check whether an index exists and is not older than its file
+fn is_fresh(index_path: &Path, path: &Path) -> io::Result<bool> {
+    let modified = match fs::metadata(index_path) {
+        Ok(metadata) => metadata.modified()?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    Ok(modified >= fs::metadata(path)?.modified()?)
+}
+
+/// an index of the records of a fastx file by name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FastqIndex {
+    records: Vec<IndexRecord>,
+    names: HashMap<String, usize>,
+}
+
+impl FastqIndex {
+    /// create an empty FastqIndex
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// get the default path of the index of a file, i.e. `{path}.fqi`
+    pub fn index_path<P: AsRef<Path>>(path: P) -> PathBuf {
+        let mut path = path.as_ref().as_os_str().to_owned();
+        path.push(".fqi");
+        PathBuf::from(path)
+    }
+
+    /// build the index of a plain or bgzipped file, return an error for other gzipped
+    /// files, fofn files and duplicated names.
+    pub fn build<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let header = read_header(&mut File::open(&path)?)?;
+        if header.starts_with(b"\x1f\x8b") && !bgzf::is_bgzf(&header) {
+            return Err(invalid(format!("{:?} is gzipped but not bgzipped", path)).into());
+        }
+        let mut records = parse_path(path.clone())?;
+        if let Paths::Readers(_) = records {
+            return Err(invalid(format!("{:?} is a fofn file", path)).into());
+        }
+        let mut index = FastqIndex::new();
+        loop {
+            let start = records.checkpoint()?;
+            let name = match records.iter_record()? {
+                Some(record) => record.head().to_string(),
+                None => break,
+            };
+            let end = records.checkpoint()?;
+            index.add(IndexRecord {
+                name,
+                offset: start.offset,
+                length: end.offset - start.offset,
+                virtual_offset: start.virtual_offset,
+            })?;
+        }
+        Ok(index)
+    }
+
+    /// load an index saved by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut index = FastqIndex::new();
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 3 && fields.len() != 4 {
+                return Err(invalid_line(i, line));
+            }
+            let parse = |field: &str| field.parse::<u64>().map_err(|_| invalid_line(i, line));
+            index.add(IndexRecord {
+                name: fields[0].to_string(),
+                offset: parse(fields[1])?,
+                length: parse(fields[2])?,
+                virtual_offset: fields.get(3).map(|field| parse(field)).transpose()?,
+            })?;
+        }
+        Ok(index)
+    }
+
+    /// save the index to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        for record in &self.records {
+            write!(out, "{}\t{}\t{}", record.name, record.offset, record.length)?;
+            if let Some(offset) = record.virtual_offset {
+                write!(out, "\t{}", offset)?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+
+    /// add a record, return an error if the name is duplicated
+    pub fn add(&mut self, record: IndexRecord) -> io::Result<()> {
+        if self.names.contains_key(&record.name) {
+            return Err(invalid(format!("duplicated name: {}", record.name)));
+        }
+        self.names.insert(record.name.clone(), self.records.len());
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// get the position of a record by name
+    pub fn get(&self, name: &str) -> Option<&IndexRecord> {
+        self.names.get(name).map(|&i| &self.records[i])
+    }
+
+    /// get the positions of all records, in the order of the file
+    pub fn records(&self) -> &[IndexRecord] {
+        &self.records
+    }
+
+    /// get the number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// check whether there is no record
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+// an indexed file
+enum Input {
+    Plain(File),
+    Bgzf(BgzfReader<File>),
+}
+
+/// a reader fetching records of a plain or bgzipped file by name
+pub struct IndexedReader {
+    input: Input,
+    index: FastqIndex,
+}
+
+impl IndexedReader {
+    /// open a file with its index `{path}.fqi`, the index is built if it doesn't exist or
+    /// is older than the file. An index modified after its file is trusted, a record not
+    /// matching the index is only detected by `fetch`.
+    pub fn open<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        let index_path = FastqIndex::index_path(&path);
+        let index = if is_fresh(&index_path, path.as_ref())? {
+            FastqIndex::load(index_path)?
+        } else {
+            FastqIndex::build(&path)?
+        };
+        Ok(IndexedReader::new(path, index)?)
+    }
+
+    /// open a file with an index
+    pub fn new<P: AsRef<Path>>(path: P, index: FastqIndex) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let header = read_header(&mut file)?;
+        let input = if bgzf::is_bgzf(&header) {
+            Input::Bgzf(BgzfReader::new(file))
+        } else if header.starts_with(b"\x1f\x8b") {
+            return Err(invalid(format!("{:?} is gzipped but not bgzipped", path)));
+        } else {
+            Input::Plain(file)
+        };
+        Ok(IndexedReader { input, index })
+    }
+
+    /// get the index
+    pub fn index(&self) -> &FastqIndex {
+        &self.index
+    }
+
+    /// fetch a record by name, including its qualities, return `None` if the name is not
+    /// in the index, or an error if the index doesn't match the file.
+    pub fn fetch(&mut self, name: &str) -> ParseResult<Option<OwnedFastx>> {
+        let record = match self.index.get(name) {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let mut data = Vec::new();
+        match &mut self.input {
+            Input::Plain(file) => {
+                file.seek(SeekFrom::Start(record.offset))?;
+                file.take(record.length).read_to_end(&mut data)?;
+            }
+            Input::Bgzf(reader) => {
+                let offset = record
+                    .virtual_offset
+                    .ok_or_else(|| invalid(format!("no virtual offset of {}", name)))?;
+                reader.seek_virtual(offset)?;
+                reader.take(record.length).read_to_end(&mut data)?;
+            }
+        }
+        if data.len() as u64 != record.length {
+            return Err(ParseError::TruncateFile(name.to_string()));
+        }
+        // a record may start with the empty lines after the previous record
+        let start = data
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(data.len());
+        let mut records = parse_reader(Cursor::new(&data[start..]))?;
+        match records.iter_record()? {
+            Some(fetched) if fetched.head() == name => Ok(Some(fetched.to_owned_fastx())),
+            _ => Err(invalid(format!("the index doesn't match the record {}", name)).into()),
+        }
+    }
+}
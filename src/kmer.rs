@@ -0,0 +1,226 @@
+//! K-mer and minimizer iteration over sequences
+//!
+//! K-mers are 2-bit encoded (`A`=0, `C`=1, `G`=2, `T`/`U`=3, case-insensitive) in a `u64` for
+//! `k <= 32` or a `u128` for `k <= 64`, k-mers containing other bases are skipped.
+//!
+//! ```
+//! use kseq::kmer::{KmerIter, MinimizerIter};
+//!
+//! let seq = b"ACGTNACGTT";
+//! let kmers: Vec<(usize, u64)> = KmerIter::new(seq, 3).collect();
+//! assert_eq!(kmers, vec![(0, 0b000110), (1, 0b011011), (5, 0b000110), (6, 0b011011), (7, 0b101111)]);
+//! let minimizers: Vec<(usize, u128)> = MinimizerIter::new(seq, 2, 3).collect();
+//! assert!(!minimizers.is_empty());
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
+
+/// an unsigned integer storing a 2-bit encoded k-mer
+pub trait KmerInt:
+    Copy
+    + Debug
+    + Ord
+    + From<u8>
+    + Not<Output = Self>
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+{
+    /// the number of bits
+    const BITS: usize;
+
+    /// an invertible hash of a k-mer masked by `mask`, used to order minimizers
+    fn hash(self, mask: Self) -> Self;
+}
+
+// the invertible integer hash of minimap2
+#[inline]
+fn hash64(key: u64, mask: u64) -> u64 {
+    let mut key = (!key).wrapping_add(key << 21) & mask;
+    key ^= key >> 24;
+    key = (key.wrapping_add(key << 3)).wrapping_add(key << 8) & mask;
+    key ^= key >> 14;
+    key = (key.wrapping_add(key << 2)).wrapping_add(key << 4) & mask;
+    key ^= key >> 28;
+    key.wrapping_add(key << 31) & mask
+}
+
+impl KmerInt for u64 {
+    const BITS: usize = 64;
+
+    fn hash(self, mask: Self) -> Self {
+        hash64(self, mask)
+    }
+}
+
+impl KmerInt for u128 {
+    const BITS: usize = 128;
+
+    fn hash(self, mask: Self) -> Self {
+        let low = hash64(self as u64, mask as u64) as u128;
+        let high = hash64((self >> 64) as u64, (mask >> 64) as u64) as u128;
+        (high << 64 | low) ^ (low << 64)
+    }
+}
+
+// 2-bit encoding of bases, 4 for ambiguous bases
+static ENCODE: [u8; 256] = {
+    let mut table = [4u8; 256];
+    table[b'A' as usize] = 0;
+    table[b'a' as usize] = 0;
+    table[b'C' as usize] = 1;
+    table[b'c' as usize] = 1;
+    table[b'G' as usize] = 2;
+    table[b'g' as usize] = 2;
+    table[b'T' as usize] = 3;
+    table[b't' as usize] = 3;
+    table[b'U' as usize] = 3;
+    table[b'u' as usize] = 3;
+    table
+};
+
+/// an iterator over the `(position, k-mer)` pairs of a sequence
+pub struct KmerIter<'a, T: KmerInt> {
+    seq: &'a [u8],
+    k: usize,
+    pos: usize,
+    // the number of valid bases ending at pos
+    valid: usize,
+    fwd: T,
+    rev: T,
+    mask: T,
+    canonical: bool,
+}
+
+impl<'a, T: KmerInt> KmerIter<'a, T> {
+    /// create an iterator over the forward k-mers,
+    /// panic if `k` is 0 or larger than the half of the bits of `T`.
+    pub fn new(seq: &'a [u8], k: usize) -> Self {
+        assert!(
+            k > 0 && k * 2 <= T::BITS,
+            "k must be in 1..={} for a {}-bit k-mer",
+            T::BITS / 2,
+            T::BITS
+        );
+        KmerIter {
+            seq,
+            k,
+            pos: 0,
+            valid: 0,
+            fwd: T::from(0),
+            rev: T::from(0),
+            mask: !T::from(0) >> (T::BITS - k * 2),
+            canonical: false,
+        }
+    }
+
+    /// create an iterator over the canonical k-mers, i.e. the smaller one
+    /// of a k-mer and its reverse complement
+    pub fn canonical(seq: &'a [u8], k: usize) -> Self {
+        KmerIter {
+            canonical: true,
+            ..Self::new(seq, k)
+        }
+    }
+
+    /// get the mask of the bits used by a k-mer
+    pub fn mask(&self) -> T {
+        self.mask
+    }
+}
+
+impl<T: KmerInt> Iterator for KmerIter<'_, T> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.seq.len() {
+            let code = ENCODE[self.seq[self.pos] as usize];
+            self.pos += 1;
+            if code > 3 {
+                self.valid = 0;
+                continue;
+            }
+            self.fwd = (self.fwd << 2 | T::from(code)) & self.mask;
+            self.rev = self.rev >> 2 | T::from(3 - code) << (self.k * 2 - 2);
+            self.valid += 1;
+            if self.valid >= self.k {
+                let kmer = if self.canonical && self.rev < self.fwd {
+                    self.rev
+                } else {
+                    self.fwd
+                };
+                return Some((self.pos - self.k, kmer));
+            }
+        }
+        None
+    }
+}
+
+/// an iterator over the `(position, k-mer)` pairs of the (w,k)-minimizers of a sequence,
+/// i.e. the canonical k-mer with the smallest hash in every window of `w` consecutive k-mers.
+/// A minimizer shared by adjacent windows is only returned once.
+pub struct MinimizerIter<'a, T: KmerInt> {
+    kmers: KmerIter<'a, T>,
+    w: usize,
+    // (index in the current run of consecutive k-mers, hash, position, k-mer)
+    window: VecDeque<(usize, T, usize, T)>,
+    run: usize,
+    last_pos: Option<usize>,
+    last_emitted: Option<usize>,
+}
+
+impl<'a, T: KmerInt> MinimizerIter<'a, T> {
+    /// create an iterator over the minimizers, panic if `w` is 0 or `k` is invalid
+    pub fn new(seq: &'a [u8], w: usize, k: usize) -> Self {
+        assert!(w > 0, "w must be larger than 0");
+        MinimizerIter {
+            kmers: KmerIter::canonical(seq, k),
+            w,
+            window: VecDeque::with_capacity(w),
+            run: 0,
+            last_pos: None,
+            last_emitted: None,
+        }
+    }
+}
+
+impl<T: KmerInt> Iterator for MinimizerIter<'_, T> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mask = self.kmers.mask();
+        let w = self.w;
+        for (pos, kmer) in self.kmers.by_ref() {
+            // an ambiguous base breaks the run of consecutive k-mers
+            if self.last_pos.is_some_and(|last| last + 1 != pos) {
+                self.window.clear();
+                self.run = 0;
+            }
+            self.last_pos = Some(pos);
+
+            let hash = kmer.hash(mask);
+            while self.window.back().is_some_and(|back| back.1 > hash) {
+                self.window.pop_back();
+            }
+            self.window.push_back((self.run, hash, pos, kmer));
+            let run = self.run;
+            while self.window.front().is_some_and(|front| front.0 + w <= run) {
+                self.window.pop_front();
+            }
+            self.run += 1;
+
+            if self.run >= w {
+                let (_, _, pos, kmer) = self.window[0];
+                if self.last_emitted != Some(pos) {
+                    self.last_emitted = Some(pos);
+                    return Some((pos, kmer));
+                }
+            }
+        }
+        None
+    }
+}
@@ -1,15 +1,40 @@
 #![doc = include_str!("../README.md")]
 // Note: kseq is inspired by fastq-rs and kseq in C
 
+use bgzf::BgzfReader;
 use flate2::read::MultiGzDecoder;
 use std::{
     fs::File,
-    io::{stdin, BufRead, BufReader, Cursor, Error, ErrorKind, Read, Result},
+    io::{self, stdin, BufRead, BufReader, Cursor, Error, ErrorKind, Read, Result, Seek, SeekFrom},
     path::Path,
 };
 
+pub mod adapter;
+pub mod bgzf;
+pub mod dedup;
+pub mod demux;
+pub mod filter;
+pub mod index;
+pub mod kmer;
+pub mod normalize;
+pub mod profile;
+pub mod quality;
 pub mod record;
-use record::{Fastx, Reader, Readers, Result as ParseResult};
+pub mod region;
+pub mod repair;
+pub mod sample;
+pub mod seq;
+pub mod sort;
+pub mod split;
+pub mod stats;
+pub mod transform;
+pub mod umi;
+pub mod writer;
+use quality::QualEncoding;
+use record::{
+    Checkpoint, ChunkedRecord, Fastx, FastxLines, FileInput, ParseError, Progress, Reader, Readers,
+    Result as ParseResult,
+};
 
 /// a reader for a single path or readers for multiple paths
 pub enum Paths<'a> {
@@ -20,6 +45,18 @@ pub enum Paths<'a> {
 impl<'a> Paths<'a> {
     // parse a reader to a Reader or Readers
     fn new(mut reader: Box<dyn BufRead + 'a>, path: &Path) -> Result<Self> {
+        if bgzf::is_bgzf(reader.fill_buf()?) {
+            // for bgzf format, records have virtual offsets
+            let mut reader = BgzfReader::new(reader);
+            return match reader.fill_buf()?.first() {
+                Some(b'@') | Some(b'>') => Ok(Paths::Reader(Reader::with_bgzf(reader))),
+                Some(_) => Self::new_fofn(Box::new(reader), path),
+                None => Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("{:?} is empty", path),
+                )),
+            };
+        }
         let mut format_bytes = [0u8; 4];
         reader.read_exact(&mut format_bytes)?;
         reader = Box::new(Cursor::new(format_bytes.to_vec()).chain(reader));
@@ -33,56 +70,195 @@ impl<'a> Paths<'a> {
 
         match format_bytes[0] {
             b'@' | b'>' => Ok(Paths::Reader(Reader::new(reader))),
-            _ => {
-                // for a fofn file
-                let mut paths = Readers::new();
-                let parent = path.parent().unwrap_or_else(|| Path::new(""));
-
-                for _line in reader.lines() {
-                    let _line = _line?;
-                    let line = _line.trim();
-                    if line.starts_with('#') || line.is_empty() {
-                        continue;
+            _ => Self::new_fofn(reader, path),
+        }
+    }
+
+    // parse a fofn file to Readers
+    fn new_fofn(reader: Box<dyn BufRead + 'a>, path: &Path) -> Result<Self> {
+        let mut paths = Readers::new();
+        let parent = path.parent().unwrap_or_else(|| Path::new(""));
+
+        for _line in reader.lines() {
+            let _line = _line?;
+            let line = _line.trim();
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let path = parent.join(line); // convert to an absolute path
+            if path.exists() {
+                match parse_path(path.clone())? {
+                    Paths::Reader(reader) => {
+                        paths.readers.push(reader);
+                        paths.paths.push(path);
                     }
-                    let path = parent.join(line); // convert to an absolute path
-                    if path.exists() {
-                        match parse_path(path)? {
-                            Paths::Reader(reader) => paths.readers.push(reader),
-                            Paths::Readers(readers) => paths.readers.extend(readers.readers),
-                        }
-                    } else {
-                        return Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!("{:?} is not a valid fastq/fasta/fofn file", path),
-                        ));
+                    Paths::Readers(readers) => {
+                        paths.readers.extend(readers.readers);
+                        paths.paths.extend(readers.paths);
                     }
                 }
-                Ok(Paths::Readers(paths))
+            } else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{:?} is not a valid fastq/fasta/fofn file", path),
+                ));
             }
         }
+        Ok(Paths::Readers(paths))
     }
 
     /// iterate a fatsx record for a Reader or Readers
-    pub fn iter_record(&mut self) -> ParseResult<Option<Fastx>> {
+    pub fn iter_record(&mut self) -> ParseResult<Option<Fastx<'_>>> {
         match self {
             Paths::Reader(t) => t.iter_record(),
             Paths::Readers(t) => t.iter_record(),
         }
     }
+
+    /// iterate a fastx record whose sequence is read line by line for a Reader or Readers,
+    /// see [`Reader::iter_record_lines`]
+    pub fn iter_record_lines(&mut self) -> ParseResult<Option<FastxLines<'_, 'a>>> {
+        match self {
+            Paths::Reader(t) => t.iter_record_lines(),
+            Paths::Readers(t) => t.iter_record_lines(),
+        }
+    }
+
+    /// iterate a fastx record whose sequence is read in chunks for a Reader or Readers,
+    /// see [`Reader::iter_record_chunks`]
+    pub fn iter_record_chunks(
+        &mut self,
+        chunk_size: usize,
+    ) -> ParseResult<Option<ChunkedRecord<'_, 'a>>> {
+        match self {
+            Paths::Reader(t) => t.iter_record_chunks(chunk_size),
+            Paths::Readers(t) => t.iter_record_chunks(chunk_size),
+        }
+    }
+
+    /// assume that every sequence and quality is in a single line for a Reader or Readers,
+    /// which is faster for single-line fastq files, default is false.
+    pub fn set_single_line(&mut self, single_line: bool) {
+        match self {
+            Paths::Reader(t) => t.set_single_line(single_line),
+            Paths::Readers(t) => t.set_single_line(single_line),
+        }
+    }
+
+    /// capture the position of the next record of a Reader or Readers, to resume reading
+    /// later by [`resume_path`]
+    pub fn checkpoint(&self) -> Result<Checkpoint> {
+        match self {
+            Paths::Reader(t) => t.checkpoint(),
+            Paths::Readers(t) => t.checkpoint(),
+        }
+    }
+
+    /// get the progress of reading a Reader or Readers, e.g. for a progress bar, which sums
+    /// up all files of a fofn file
+    pub fn progress(&self) -> Progress {
+        match self {
+            Paths::Reader(t) => t.progress(),
+            Paths::Readers(t) => t.progress(),
+        }
+    }
+
+    /// detect the quality encoding from the first `n` records for a Reader or Readers
+    pub fn detect_qual_encoding(&mut self, n: usize) -> ParseResult<Option<QualEncoding>> {
+        match self {
+            Paths::Reader(t) => t.detect_qual_encoding(n),
+            Paths::Readers(t) => t.detect_qual_encoding(n),
+        }
+    }
 }
 
 /// parse path to a Reader or Readers
 pub fn parse_path<'a, P: AsRef<Path> + 'a>(path: P) -> Result<Paths<'a>> {
     let path = path.as_ref();
-    let reader: Box<dyn BufRead> = if path == Path::new("-") {
+    if path == Path::new("-") {
         if atty::is(atty::Stream::Stdin) {
             return Err(Error::new(ErrorKind::InvalidInput, "Missing input"));
         }
-        Box::new(BufReader::with_capacity(65536, stdin()))
-    } else {
-        Box::new(BufReader::with_capacity(65536, File::open(path)?))
+        return Paths::new(Box::new(BufReader::with_capacity(65536, stdin())), path);
+    }
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let file = FileInput::new(file);
+    let position = file.position();
+    let mut paths = Paths::new(Box::new(BufReader::with_capacity(65536, file)), path)?;
+    if let Paths::Reader(reader) = &mut paths {
+        reader.set_file(position, size);
+    }
+    Ok(paths)
+}
+
+/// parse path to a Reader or Readers, and resume reading from a checkpoint captured by
+/// [`Paths::checkpoint`]. Plain files are seeked to the offset, bgzipped files are seeked
+/// to the virtual offset, and other gzipped files are decompressed up to the offset. The files
+/// of a fofn file before the checkpoint are counted as read by `progress`, except for their
+/// uncompressed bytes.
+pub fn resume_path<'a, P: AsRef<Path> + 'a>(path: P, checkpoint: &Checkpoint) -> Result<Paths<'a>> {
+    let path = path.as_ref().to_path_buf();
+    let mut paths = parse_path(path.clone())?;
+    match &mut paths {
+        Paths::Reader(reader) => *reader = open_at(&path, checkpoint)?,
+        Paths::Readers(readers) => {
+            let index = checkpoint.file_index;
+            if index >= readers.readers.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("{:?} has no file {}", path, index),
+                ));
+            }
+            // the files before the checkpoint are not read again
+            readers.readers[..index]
+                .iter_mut()
+                .for_each(Reader::set_finished);
+            readers.readers[index] = open_at(&readers.paths[index], checkpoint)?;
+            readers.index = index;
+        }
+    }
+    Ok(paths)
+}
+
+// open a fastx file at a checkpoint
+fn open_at(path: &Path, checkpoint: &Checkpoint) -> Result<Reader<'static>> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let file = FileInput::new(file);
+    let position = file.position();
+    let mut file = BufReader::with_capacity(65536, file);
+    let header = file.fill_buf()?;
+    let (gz, bgzf) = (header.starts_with(b"\x1f\x8b"), bgzf::is_bgzf(header));
+    let mut reader = match checkpoint.virtual_offset {
+        Some(offset) if bgzf => {
+            let mut bgzf = BgzfReader::new(file);
+            bgzf.seek_virtual(offset)?;
+            Reader::with_bgzf(bgzf)
+        }
+        _ if gz => {
+            let mut reader: Box<dyn BufRead> = if bgzf {
+                Box::new(BgzfReader::new(file))
+            } else {
+                Box::new(BufReader::with_capacity(65536, MultiGzDecoder::new(file)))
+            };
+            let skipped = io::copy(&mut (&mut reader).take(checkpoint.offset), &mut io::sink())?;
+            if skipped < checkpoint.offset {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("{:?} is shorter than the checkpoint", path),
+                ));
+            }
+            Reader::new(reader)
+        }
+        _ => {
+            file.seek(SeekFrom::Start(checkpoint.offset))?;
+            Reader::new(Box::new(file))
+        }
     };
-    Paths::new(reader, path)
+    reader.set_checkpoint(checkpoint);
+    reader.set_file(position, size);
+    Ok(reader)
 }
 
 /// parse reader to a Reader or Readers
@@ -92,3 +268,25 @@ pub fn parse_reader<'a, R: Read + 'a>(reader: R) -> Result<Paths<'a>> {
         Path::new(""),
     )
 }
+
+// iterate over the records of two paired Readers together, return an error if they have
+// different numbers of records
+pub(crate) fn each_pair<F>(paths1: &mut Paths, paths2: &mut Paths, mut f: F) -> ParseResult<()>
+where
+    F: FnMut(&Fastx, &Fastx) -> ParseResult<()>,
+{
+    let unpaired = || {
+        ParseError::Io(Error::new(
+            ErrorKind::InvalidData,
+            "paired files have different numbers of records",
+        ))
+    };
+    while let Some(r1) = paths1.iter_record()? {
+        let r2 = paths2.iter_record()?.ok_or_else(unpaired)?;
+        f(&r1, &r2)?;
+    }
+    match paths2.iter_record()? {
+        Some(_) => Err(unpaired()),
+        None => Ok(()),
+    }
+}
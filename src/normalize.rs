@@ -0,0 +1,96 @@
+//! Normalisation of fasta/fastq files into a canonical format
+//!
+//! Records are rewritten with a single header line, sequences and qualities in a single line
+//! (or wrapped at a fixed width), `\r` removed, and optionally in upper case. Fasta records
+//! get synthetic qualities when written as fastq, and fastq records lose their qualities when
+//! written as fasta.
+
+use crate::record::{Fastx, OwnedFastx, Result as ParseResult};
+use crate::writer::Writer;
+use crate::Paths;
+use std::io::Write;
+
+/// the format of normalised records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// keep the format of each input record
+    Auto,
+    /// write all records as fasta
+    Fasta,
+    /// write all records as fastq
+    Fastq,
+}
+
+/// options of normalisation
+#[derive(Debug, Clone)]
+pub struct Normalizer {
+    /// the output format, default is `Format::Auto`
+    pub format: Format,
+    /// the maximum line width of sequences and qualities, default is 0 for a single line
+    pub line_width: usize,
+    /// convert sequences to upper case, default is false
+    pub uppercase: bool,
+    /// keep the text after `+` in the separator line, default is false
+    pub keep_sep: bool,
+    /// the quality character of synthetic qualities, default is `I` (Phred 40)
+    pub fake_qual: u8,
+}
+
+impl Default for Normalizer {
+    fn default() -> Self {
+        Normalizer {
+            format: Format::Auto,
+            line_width: 0,
+            uppercase: false,
+            keep_sep: false,
+            fake_qual: b'I',
+        }
+    }
+}
+
+impl Normalizer {
+    /// normalise a record
+    pub fn normalize(&self, record: &Fastx) -> OwnedFastx {
+        let mut owned = OwnedFastx {
+            head: record.head().to_string(),
+            des: record.des().to_string(),
+            seq: record.seq().to_string(),
+            sep: String::new(),
+            qual: String::new(),
+        };
+        if self.uppercase {
+            owned.unmask();
+        }
+        let fastq = match self.format {
+            Format::Auto => record.is_fastq(),
+            Format::Fasta => false,
+            Format::Fastq => true,
+        };
+        if fastq {
+            owned.sep = if self.keep_sep {
+                record.sep().to_string()
+            } else {
+                "+".to_string()
+            };
+            owned.qual = if record.is_fastq() {
+                record.qual().to_string()
+            } else {
+                (self.fake_qual as char).to_string().repeat(owned.seq.len())
+            };
+        }
+        owned
+    }
+
+    /// normalise all records of a Reader or Readers and write them to a Writer,
+    /// return the number of records written.
+    pub fn run<W: Write>(&self, paths: &mut Paths, writer: &mut Writer<W>) -> ParseResult<u64> {
+        writer.set_line_width(self.line_width);
+        let mut n = 0;
+        while let Some(record) = paths.iter_record()? {
+            writer.write_record(&self.normalize(&record))?;
+            n += 1;
+        }
+        writer.flush()?;
+        Ok(n)
+    }
+}
@@ -0,0 +1,339 @@
+//! Per-position quality and base composition profiles, similar to FastQC
+//!
+//! A [`Profile`] is fed with records and reports per-position quality boxplots, per-position
+//! base composition, the length distribution, the GC distribution and a duplication estimate,
+//! which can be written as TSV, or as JSON with the `serde` feature.
+
+use crate::record::FastxRecord;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::io::{Result, Write};
+
+// the number of Phred scores (0..=93) of printable ASCII characters
+const NUM_QUALS: usize = 94;
+// the maximum number of distinct sequences tracked for duplication, as in FastQC
+const DUP_TRACKED: usize = 100_000;
+// sequences longer than this are truncated to DUP_PREFIX for duplication, as in FastQC
+const DUP_MAX_LEN: usize = 75;
+// the length of the sequence prefix used for duplication of long sequences, as in FastQC
+const DUP_PREFIX: usize = 50;
+/// the labels of duplication levels, the last level includes all higher levels
+pub const DUP_LEVELS: [&str; 10] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", ">=10"];
+
+/// the quality boxplot of a position
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualBox {
+    /// mean Phred score
+    pub mean: f64,
+    /// median Phred score
+    pub median: u8,
+    /// 25th percentile
+    pub lower_quartile: u8,
+    /// 75th percentile
+    pub upper_quartile: u8,
+    /// 10th percentile
+    pub percentile_10: u8,
+    /// 90th percentile
+    pub percentile_90: u8,
+}
+
+/// all distributions of a Profile, see the getters of [`Profile`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// number of records
+    pub num_seqs: u64,
+    /// the quality boxplot of each position
+    pub per_position_quality: Vec<QualBox>,
+    /// the percentages of A, C, G, T and other bases of each position
+    pub per_position_base_composition: Vec<[f64; 5]>,
+    /// the sequence length distribution, length -> count
+    pub length_distribution: BTreeMap<usize, u64>,
+    /// the count of records of each GC% (0..=100)
+    pub gc_distribution: Vec<u64>,
+    /// the number of distinct tracked sequences of each level of `DUP_LEVELS`
+    pub duplication_levels: [u64; 10],
+    /// the percentage of sequences remaining if deduplicated
+    pub dedup_remaining: f64,
+}
+
+/// an accumulator of per-position and per-record distributions
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// the ASCII offset of quality scores, default is 33
+    pub offset: u8,
+    num_seqs: u64,
+    // per-position Phred score counts
+    quals: Vec<[u64; NUM_QUALS]>,
+    // per-position base counts of A, C, G, T and others
+    bases: Vec<[u64; 5]>,
+    lengths: BTreeMap<usize, u64>,
+    // counts of records by their GC content (%)
+    gc: Vec<u64>,
+    // hash of a tracked sequence -> count
+    dup: HashMap<u64, u64>,
+    dup_total: u64,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Profile {
+    /// create an empty Profile
+    pub fn new() -> Self {
+        Profile {
+            offset: 33,
+            num_seqs: 0,
+            quals: Vec::new(),
+            bases: Vec::new(),
+            lengths: BTreeMap::new(),
+            gc: vec![0; 101],
+            dup: HashMap::new(),
+            dup_total: 0,
+        }
+    }
+
+    /// add a record to this Profile
+    pub fn update<R: FastxRecord>(&mut self, record: &R) {
+        let seq = record.seq().as_bytes();
+        self.num_seqs += 1;
+        *self.lengths.entry(seq.len()).or_insert(0) += 1;
+
+        if self.bases.len() < seq.len() {
+            self.bases.resize(seq.len(), [0; 5]);
+        }
+        let mut gc = 0;
+        let mut acgt: usize = 0;
+        for (counts, &b) in self.bases.iter_mut().zip(seq) {
+            let idx = match b {
+                b'A' | b'a' => 0,
+                b'C' | b'c' => 1,
+                b'G' | b'g' => 2,
+                b'T' | b't' => 3,
+                _ => 4,
+            };
+            counts[idx] += 1;
+            if idx < 4 {
+                acgt += 1;
+                if idx == 1 || idx == 2 {
+                    gc += 1;
+                }
+            }
+        }
+        if let Some(pct) = (gc * 100 + acgt / 2).checked_div(acgt) {
+            self.gc[pct] += 1;
+        }
+
+        let qual = record.qual().as_bytes();
+        if self.quals.len() < qual.len() {
+            self.quals.resize(qual.len(), [0; NUM_QUALS]);
+        }
+        for (counts, &q) in self.quals.iter_mut().zip(qual) {
+            counts[(q.saturating_sub(self.offset) as usize).min(NUM_QUALS - 1)] += 1;
+        }
+
+        // track the first distinct sequences, later sequences are only counted if tracked
+        let key = if seq.len() > DUP_MAX_LEN {
+            &seq[..DUP_PREFIX]
+        } else {
+            seq
+        };
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        let hash = hasher.finish();
+        if let Some(count) = self.dup.get_mut(&hash) {
+            *count += 1;
+            self.dup_total += 1;
+        } else if self.dup.len() < DUP_TRACKED {
+            self.dup.insert(hash, 1);
+            self.dup_total += 1;
+        }
+    }
+
+    /// merge another Profile into this Profile, the duplication estimate of
+    /// the merged Profile is approximate once the tracked sequences are full
+    pub fn merge(&mut self, other: &Profile) {
+        self.num_seqs += other.num_seqs;
+        if self.quals.len() < other.quals.len() {
+            self.quals.resize(other.quals.len(), [0; NUM_QUALS]);
+        }
+        for (a, b) in self.quals.iter_mut().zip(&other.quals) {
+            a.iter_mut().zip(b.iter()).for_each(|(x, y)| *x += y);
+        }
+        if self.bases.len() < other.bases.len() {
+            self.bases.resize(other.bases.len(), [0; 5]);
+        }
+        for (a, b) in self.bases.iter_mut().zip(&other.bases) {
+            a.iter_mut().zip(b.iter()).for_each(|(x, y)| *x += y);
+        }
+        for (&len, &count) in &other.lengths {
+            *self.lengths.entry(len).or_insert(0) += count;
+        }
+        self.gc.iter_mut().zip(&other.gc).for_each(|(x, y)| *x += y);
+        for (&hash, &count) in &other.dup {
+            if let Some(c) = self.dup.get_mut(&hash) {
+                *c += count;
+                self.dup_total += count;
+            } else if self.dup.len() < DUP_TRACKED {
+                self.dup.insert(hash, count);
+                self.dup_total += count;
+            }
+        }
+    }
+
+    /// get the number of records
+    pub fn num_seqs(&self) -> u64 {
+        self.num_seqs
+    }
+
+    /// get the quality boxplot of each position, empty for fasta records
+    pub fn qual_boxes(&self) -> Vec<QualBox> {
+        self.quals.iter().map(qual_box).collect()
+    }
+
+    /// get the percentages of A, C, G, T and other bases of each position
+    pub fn base_composition(&self) -> Vec<[f64; 5]> {
+        self.bases
+            .iter()
+            .map(|counts| {
+                let total: u64 = counts.iter().sum();
+                let mut pct = [0.0; 5];
+                pct.iter_mut()
+                    .zip(counts.iter())
+                    .for_each(|(p, &c)| *p = c as f64 * 100.0 / total as f64);
+                pct
+            })
+            .collect()
+    }
+
+    /// get the sequence length distribution, length -> count
+    pub fn length_distribution(&self) -> &BTreeMap<usize, u64> {
+        &self.lengths
+    }
+
+    /// get the distribution of per-record GC content, the count of records of each GC% (0..=100)
+    pub fn gc_distribution(&self) -> &[u64] {
+        &self.gc
+    }
+
+    /// get the number of distinct tracked sequences of each duplication level, see `DUP_LEVELS`
+    pub fn duplication_levels(&self) -> [u64; 10] {
+        let mut levels = [0; 10];
+        for &count in self.dup.values() {
+            levels[(count as usize).min(10) - 1] += 1;
+        }
+        levels
+    }
+
+    /// get the percentage of sequences remaining if deduplicated, estimated from the tracked sequences
+    pub fn dedup_remaining(&self) -> f64 {
+        if self.dup_total == 0 {
+            100.0
+        } else {
+            self.dup.len() as f64 * 100.0 / self.dup_total as f64
+        }
+    }
+
+    /// get all distributions
+    pub fn report(&self) -> Report {
+        Report {
+            num_seqs: self.num_seqs,
+            per_position_quality: self.qual_boxes(),
+            per_position_base_composition: self.base_composition(),
+            length_distribution: self.lengths.clone(),
+            gc_distribution: self.gc.clone(),
+            duplication_levels: self.duplication_levels(),
+            dedup_remaining: self.dedup_remaining(),
+        }
+    }
+
+    /// write all distributions as a JSON object of [`Report`]
+    #[cfg(feature = "serde")]
+    pub fn write_json<W: Write>(&self, mut w: W) -> Result<()> {
+        serde_json::to_writer_pretty(&mut w, &self.report())?;
+        writeln!(w)
+    }
+
+    /// write all distributions as TSV tables, each table starts with a `>>name` line
+    pub fn write_tsv<W: Write>(&self, mut w: W) -> Result<()> {
+        writeln!(w, ">>per_position_quality")?;
+        writeln!(
+            w,
+            "#position\tmean\tmedian\tlower_quartile\tupper_quartile\tpercentile_10\tpercentile_90"
+        )?;
+        for (i, b) in self.qual_boxes().iter().enumerate() {
+            writeln!(
+                w,
+                "{}\t{:.2}\t{}\t{}\t{}\t{}\t{}",
+                i + 1,
+                b.mean,
+                b.median,
+                b.lower_quartile,
+                b.upper_quartile,
+                b.percentile_10,
+                b.percentile_90
+            )?;
+        }
+        writeln!(w, ">>per_position_base_composition")?;
+        writeln!(w, "#position\tA\tC\tG\tT\tN")?;
+        for (i, p) in self.base_composition().iter().enumerate() {
+            writeln!(
+                w,
+                "{}\t{:.2}\t{:.2}\t{:.2}\t{:.2}\t{:.2}",
+                i + 1,
+                p[0],
+                p[1],
+                p[2],
+                p[3],
+                p[4]
+            )?;
+        }
+        writeln!(w, ">>length_distribution")?;
+        writeln!(w, "#length\tcount")?;
+        for (len, count) in &self.lengths {
+            writeln!(w, "{}\t{}", len, count)?;
+        }
+        writeln!(w, ">>gc_distribution")?;
+        writeln!(w, "#gc\tcount")?;
+        for (gc, count) in self.gc.iter().enumerate() {
+            writeln!(w, "{}\t{}", gc, count)?;
+        }
+        writeln!(w, ">>duplication_levels")?;
+        writeln!(w, "#level\tcount")?;
+        for (i, count) in self.duplication_levels().iter().enumerate() {
+            writeln!(w, "{}\t{}", DUP_LEVELS[i], count)?;
+        }
+        writeln!(w, "#dedup_remaining\t{:.2}", self.dedup_remaining())
+    }
+}
+
+// get the boxplot of a histogram of Phred scores
+fn qual_box(counts: &[u64; NUM_QUALS]) -> QualBox {
+    let total: u64 = counts.iter().sum();
+    let sum: u64 = counts.iter().enumerate().map(|(q, &c)| q as u64 * c).sum();
+    // the score at a fraction of all scores
+    let at = |fraction: f64| {
+        let rank = ((total as f64 * fraction).ceil() as u64).max(1);
+        let mut seen = 0;
+        counts
+            .iter()
+            .position(|&c| {
+                seen += c;
+                seen >= rank
+            })
+            .unwrap_or(0) as u8
+    };
+    QualBox {
+        mean: sum as f64 / total as f64,
+        median: at(0.5),
+        lower_quartile: at(0.25),
+        upper_quartile: at(0.75),
+        percentile_10: at(0.1),
+        percentile_90: at(0.9),
+    }
+}
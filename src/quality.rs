@@ -0,0 +1,105 @@
+//! Phred quality score decoding and statistics
+
+/// the quality encoding of a fastq file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualEncoding {
+    /// Sanger/Illumina 1.8+, Phred+33
+    Sanger,
+    /// Solexa/Illumina 1.0, Solexa+64
+    Solexa,
+    /// Illumina 1.3+, Phred+64
+    Illumina13,
+}
+
+impl QualEncoding {
+    /// get the ASCII offset of this encoding, note that Solexa scores decoded with this offset
+    /// are not Phred scores, use [`QualEncoding::scores`] to convert them
+    pub fn offset(&self) -> u8 {
+        match self {
+            QualEncoding::Sanger => 33,
+            QualEncoding::Solexa | QualEncoding::Illumina13 => 64,
+        }
+    }
+
+    /// iterate over the Phred scores of a quality string in this encoding, Solexa scores are
+    /// converted to Phred scores
+    pub fn scores<'a>(&self, qual: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+        let encoding = *self;
+        qual.iter().map(move |&q| match encoding {
+            QualEncoding::Solexa => solexa_to_phred(q as i32 - 64),
+            _ => q.saturating_sub(encoding.offset()),
+        })
+    }
+
+    /// guess the encoding from the minimum and maximum quality characters observed,
+    /// a range that fits both Phred+33 and Phred+64 is reported as Sanger.
+    pub fn guess(min: u8, max: u8) -> Self {
+        if min < b';' {
+            QualEncoding::Sanger
+        } else if min < b'@' {
+            QualEncoding::Solexa
+        } else if max <= b'J' {
+            QualEncoding::Sanger
+        } else {
+            QualEncoding::Illumina13
+        }
+    }
+}
+
+/// iterate over the Phred scores of a quality string, characters below `offset` are decoded as 0
+pub fn phred(qual: &[u8], offset: u8) -> impl Iterator<Item = u8> + '_ {
+    qual.iter().map(move |&q| q.saturating_sub(offset))
+}
+
+/// convert a Solexa score, the log-odds of an error which can be as low as -5, to the nearest
+/// Phred score
+pub fn solexa_to_phred(score: i32) -> u8 {
+    (10.0 * (10f64.powf(score as f64 / 10.0) + 1.0).log10()).round() as u8
+}
+
+/// convert a Phred score to an error probability
+#[inline]
+pub fn error_prob(score: u8) -> f64 {
+    10f64.powf(-(score as f64) / 10.0)
+}
+
+/// get the mean Phred score of a quality string, or `None` if it is empty
+pub fn mean(qual: &[u8], offset: u8) -> Option<f64> {
+    if qual.is_empty() {
+        return None;
+    }
+    let sum: u64 = phred(qual, offset).map(|q| q as u64).sum();
+    Some(sum as f64 / qual.len() as f64)
+}
+
+/// get the median Phred score of a quality string, or `None` if it is empty
+pub fn median(qual: &[u8], offset: u8) -> Option<f64> {
+    if qual.is_empty() {
+        return None;
+    }
+    let mut counts = [0usize; 256];
+    phred(qual, offset).for_each(|q| counts[q as usize] += 1);
+
+    // the (rank)th smallest score, 0-based
+    let nth = |rank: usize| {
+        let mut seen = 0;
+        counts
+            .iter()
+            .position(|&c| {
+                seen += c;
+                seen > rank
+            })
+            .unwrap_or(0)
+    };
+    let n = qual.len();
+    if n % 2 == 1 {
+        Some(nth(n / 2) as f64)
+    } else {
+        Some((nth(n / 2 - 1) + nth(n / 2)) as f64 / 2.0)
+    }
+}
+
+/// get the expected number of errors of a quality string, i.e. the sum of the error probabilities
+pub fn expected_errors(qual: &[u8], offset: u8) -> f64 {
+    phred(qual, offset).map(error_prob).sum()
+}
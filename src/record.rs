@@ -1,6 +1,12 @@
+use crate::bgzf::BgzfReader;
+use crate::quality::{self, QualEncoding};
 use std::{
+    cell::Cell,
     error, fmt,
-    io::{self, ErrorKind},
+    io::{self, BufRead, Cursor, ErrorKind, Read, Seek, SeekFrom},
+    mem,
+    path::PathBuf,
+    rc::Rc,
     str,
 };
 
@@ -19,6 +25,9 @@ pub enum ParseError {
     InvalidFasta(String),
     /// Not a valid fastq record, the record start with `@` but the sequence and quality lengths are not equal or 0
     InvalidFastq(String),
+    /// Lines are terminated by `\r` only (old Mac line endings), which is not supported.
+    /// It is detected by the first line of a file, a `\r` inside a later line is kept.
+    InvalidLineEnding(String),
 }
 
 impl fmt::Display for ParseError {
@@ -37,6 +46,9 @@ impl fmt::Display for ParseError {
             ParseError::InvalidFastq(record) => {
                 write!(f, "Not a valid fastq record: {}", record)
             }
+            ParseError::InvalidLineEnding(record) => {
+                write!(f, "Not a valid line ending `\\r`: {}", record)
+            }
         }
     }
 }
@@ -49,6 +61,44 @@ impl From<io::Error> for ParseError {
 
 impl error::Error for ParseError {}
 
+/// common accessors of a borrowed or an owned fastx record
+pub trait FastxRecord {
+    /// get sequence id/identifier
+    fn head(&self) -> &str;
+
+    /// get sequence description/comment, including the leading whitespace
+    fn des(&self) -> &str;
+
+    /// get sequence
+    fn seq(&self) -> &str;
+
+    /// get separator, `""` for a fasta record
+    fn sep(&self) -> &str;
+
+    /// get quality scores, `""` for a fasta record
+    fn qual(&self) -> &str;
+
+    /// get sequence length
+    fn len(&self) -> usize {
+        self.seq().len()
+    }
+
+    /// check whether a fastx record is empty
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// check whether a fastx record is a fastq record
+    fn is_fastq(&self) -> bool {
+        !self.sep().is_empty()
+    }
+
+    /// check whether a fastx record is a fasta record
+    fn is_fasta(&self) -> bool {
+        !self.is_fastq()
+    }
+}
+
 /// a structure representing the sequence in a fastx file
 pub struct Fastx<'a> {
     _head: usize,
@@ -123,9 +173,39 @@ impl Fastx<'_> {
         (!self._data.is_empty()) && self._data[0] == b'@'
     }
 
+    /// copy this record to an owned record
+    pub fn to_owned_fastx(&self) -> OwnedFastx {
+        OwnedFastx::from(self)
+    }
+
+    /// iterate over the Phred scores of quality, decoded with the ASCII `offset` (33 or 64)
+    pub fn qual_scores(&self, offset: u8) -> impl Iterator<Item = u8> + '_ {
+        quality::phred(self.qual().as_bytes(), offset)
+    }
+
+    /// iterate over the error probabilities of quality
+    pub fn error_probs(&self, offset: u8) -> impl Iterator<Item = f64> + '_ {
+        self.qual_scores(offset).map(quality::error_prob)
+    }
+
+    /// get the mean Phred score, `None` for a fasta record
+    pub fn mean_qual(&self, offset: u8) -> Option<f64> {
+        quality::mean(self.qual().as_bytes(), offset)
+    }
+
+    /// get the median Phred score, `None` for a fasta record
+    pub fn median_qual(&self, offset: u8) -> Option<f64> {
+        quality::median(self.qual().as_bytes(), offset)
+    }
+
+    /// get the expected number of errors, 0 for a fasta record
+    pub fn expected_errors(&self, offset: u8) -> f64 {
+        quality::expected_errors(self.qual().as_bytes(), offset)
+    }
+
     /// check a fastq record is valid
     fn validate_fastq(&self) -> bool {
-        self.is_fastq() && !self.is_empty() && self._seq - self._des == self._qual - self._sep && self._head > 1
+        self.sep().starts_with('+') && !self.is_empty() && self._seq - self._des == self._qual - self._sep && self._head > 1
     }
 
     /// check a fasta record is valid
@@ -134,19 +214,596 @@ impl Fastx<'_> {
     }
 }
 
+impl FastxRecord for Fastx<'_> {
+    fn head(&self) -> &str {
+        Fastx::head(self)
+    }
+
+    fn des(&self) -> &str {
+        Fastx::des(self)
+    }
+
+    fn seq(&self) -> &str {
+        Fastx::seq(self)
+    }
+
+    fn sep(&self) -> &str {
+        Fastx::sep(self)
+    }
+
+    fn qual(&self) -> &str {
+        Fastx::qual(self)
+    }
+}
+
+/// an owned fastx record, which outlives the shared buffer of a Reader
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnedFastx {
+    /// sequence id/identifier
+    pub head: String,
+    /// sequence description/comment, including the leading whitespace
+    pub des: String,
+    /// sequence
+    pub seq: String,
+    /// separator, starts with `+` for a fastq record and is empty for a fasta record
+    pub sep: String,
+    /// quality scores, empty for a fasta record
+    pub qual: String,
+}
+
+impl OwnedFastx {
+    /// keep only the bases (and qualities) in `start..end`
+    pub fn trim(&mut self, start: usize, end: usize) {
+        let end = end.min(self.seq.len());
+        let start = start.min(end);
+        self.seq.truncate(end);
+        self.seq.drain(..start);
+        if !self.qual.is_empty() {
+            self.qual.truncate(end);
+            self.qual.drain(..start);
+        }
+    }
+}
+
+impl From<&Fastx<'_>> for OwnedFastx {
+    fn from(fastx: &Fastx<'_>) -> Self {
+        OwnedFastx {
+            head: fastx.head().to_string(),
+            des: fastx.des().to_string(),
+            seq: fastx.seq().to_string(),
+            sep: fastx.sep().to_string(),
+            qual: fastx.qual().to_string(),
+        }
+    }
+}
+
+impl FastxRecord for OwnedFastx {
+    fn head(&self) -> &str {
+        &self.head
+    }
+
+    fn des(&self) -> &str {
+        &self.des
+    }
+
+    fn seq(&self) -> &str {
+        &self.seq
+    }
+
+    fn sep(&self) -> &str {
+        &self.sep
+    }
+
+    fn qual(&self) -> &str {
+        &self.qual
+    }
+}
+
+/// a record whose sequence is read as lines borrowed from the buffer of the Reader, returned
+/// by `iter_record_lines`. The sequence of a fasta record is never copied, a fastq record is
+/// read at once and its sequence is returned as a single line.
+pub struct FastxLines<'r, 'a> {
+    reader: &'r mut Reader<'a>,
+    head: usize,
+    des: usize,
+    // whether any base is found
+    has_bases: bool,
+}
+
+impl FastxLines<'_, '_> {
+    /// get sequence id/identifier
+    pub fn head(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.reader.data[1..self.head]) }
+    }
+
+    /// get sequence description/comment
+    pub fn des(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.reader.data[self.head..self.des]) }
+    }
+
+    /// check whether a fastx record is a fasta record
+    pub fn is_fasta(&self) -> bool {
+        self.reader.data[0] == b'>'
+    }
+
+    /// check whether a fastx record is a fastq record
+    pub fn is_fastq(&self) -> bool {
+        self.reader.data[0] == b'@'
+    }
+
+    /// read the lines of sequence in the buffer of the Reader, return `None` at the end of
+    /// sequence. A line crossing the end of the buffer is split into two lines, the second
+    /// of which is returned by the next call.
+    pub fn next_lines(&mut self) -> Result<Option<SeqLines<'_>>> {
+        let reader = &mut *self.reader;
+        let data = match reader.stream {
+            Some(Stream::Fasta) => loop {
+                let n = reader.read_fasta_lines()?;
+                if n == 0 {
+                    reader.stream = None;
+                    if !self.has_bases {
+                        let head = String::from_utf8_lossy(&reader.data[1..self.head]).into_owned();
+                        return Err(if reader.has_data_left()? {
+                            ParseError::InvalidFasta(head)
+                        } else {
+                            ParseError::TruncateFile(head)
+                        });
+                    }
+                    return Ok(None);
+                }
+                // skip the lines without bases, e.g. the rest of a `\r\n` split by the buffer
+                let is_base = |&b: &u8| b != b'\n' && b != b'\r';
+                if reader.reader.fill_buf()?[..n].iter().any(is_base) {
+                    self.has_bases = true;
+                    break &reader.reader.fill_buf()?[..n];
+                }
+            },
+            Some(Stream::Fastq { pos, end }) => {
+                reader.stream = None;
+                &reader.data[pos..end]
+            }
+            None => return Ok(None),
+        };
+        Ok(Some(SeqLines { data }))
+    }
+}
+
+/// an iterator over the non-empty lines of a sequence without newlines, see
+/// [`FastxLines::next_lines`]. Lines are bytes, as the end of the buffer may split a character.
+pub struct SeqLines<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for SeqLines<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        while !self.data.is_empty() {
+            let (line, rest) = match memchr::memchr(b'\n', self.data) {
+                Some(i) => (&self.data[..i], &self.data[i + 1..]),
+                None => (self.data, &self.data[self.data.len()..]),
+            };
+            self.data = rest;
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if !line.is_empty() {
+                return Some(line);
+            }
+        }
+        None
+    }
+}
+
+/// a record whose sequence is read in chunks of a bounded size, returned by
+/// `iter_record_chunks`. The sequence of a fasta record is never read into memory
+/// at once, a fastq record is read at once and only its sequence is returned in chunks.
+pub struct ChunkedRecord<'r, 'a> {
+    reader: &'r mut Reader<'a>,
+    head: usize,
+    des: usize,
+    chunk_size: usize,
+    len: usize,
+    // the bytes of a character split at the end of the last chunk, kept for the next chunk
+    split: usize,
+}
+
+impl ChunkedRecord<'_, '_> {
+    /// get sequence id/identifier
+    pub fn head(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.reader.data[1..self.head]) }
+    }
+
+    /// get sequence description/comment
+    pub fn des(&self) -> &str {
+        unsafe { str::from_utf8_unchecked(&self.reader.data[self.head..self.des]) }
+    }
+
+    /// check whether a fastx record is a fasta record
+    pub fn is_fasta(&self) -> bool {
+        self.reader.data[0] == b'>'
+    }
+
+    /// check whether a fastx record is a fastq record
+    pub fn is_fastq(&self) -> bool {
+        self.reader.data[0] == b'@'
+    }
+
+    /// get the number of bases returned by `next_chunk` so far
+    pub fn num_bases(&self) -> usize {
+        self.len
+    }
+
+    /// read the next chunk of sequence without newlines, which has at most `chunk_size`
+    /// bytes and is cut at a character boundary (a character longer than `chunk_size` is
+    /// a chunk), return `None` at the end of sequence.
+    pub fn next_chunk(&mut self) -> Result<Option<&str>> {
+        let reader = &mut *self.reader;
+        let chunk = match reader.stream {
+            Some(Stream::Fasta) => {
+                let mut full = reader.read_fasta_chunk(self.chunk_size, self.split)?;
+                while full && char_boundary(&reader.chunk) == 0 {
+                    // read the rest of a character longer than chunk_size
+                    let len = reader.chunk.len();
+                    full = reader.read_fasta_chunk(len + 1, len)?;
+                }
+                if !full {
+                    reader.stream = None;
+                    if self.len + reader.chunk.len() == 0 {
+                        let head = String::from_utf8_lossy(&reader.data[1..self.head]).into_owned();
+                        return Err(if reader.has_data_left()? {
+                            ParseError::InvalidFasta(head)
+                        } else {
+                            ParseError::TruncateFile(head)
+                        });
+                    }
+                }
+                let end = if full {
+                    char_boundary(&reader.chunk)
+                } else {
+                    reader.chunk.len()
+                };
+                self.split = reader.chunk.len() - end;
+                &reader.chunk[..end]
+            }
+            Some(Stream::Fastq { pos, end }) => {
+                let mut next = end.min(pos + self.chunk_size);
+                if next < end {
+                    next = match char_boundary(&reader.data[pos..next]) {
+                        0 => end.min(pos + utf8_len(reader.data[pos])),
+                        n => pos + n,
+                    };
+                }
+                reader.stream = if next < end {
+                    Some(Stream::Fastq { pos: next, end })
+                } else {
+                    None
+                };
+                &reader.data[pos..next]
+            }
+            None => return Ok(None),
+        };
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        self.len += chunk.len();
+        // chunks are cut at character boundaries of the lines, which are UTF-8 as assumed
+        // by Fastx
+        Ok(Some(unsafe { str::from_utf8_unchecked(chunk) }))
+    }
+}
+
+// get the end of the last complete character of UTF-8 bytes cut at any byte
+fn char_boundary(bytes: &[u8]) -> usize {
+    // the last byte which is not a continuation byte starts the last character
+    let start = bytes.iter().rposition(|&b| (b as i8) >= -0x40).unwrap_or(0);
+    match bytes.get(start) {
+        Some(&b) if start + utf8_len(b) > bytes.len() => start,
+        _ => bytes.len(),
+    }
+}
+
+// get the length of a UTF-8 character from its first byte
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    }
+}
+
+/// a position between records of a Reader or Readers, from which reading can be resumed
+/// by [`resume_path`](crate::resume_path)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// the index of the current file of a fofn file, 0 for a single file
+    pub file_index: usize,
+    /// the number of records read, from all files of a fofn file
+    pub record_index: u64,
+    /// the offset of the next record in the uncompressed stream of the current file
+    pub offset: u64,
+    /// the BGZF virtual offset of the next record, `None` if the file is not bgzipped
+    /// or the offset is not known, e.g. while reading the records put back by
+    /// `detect_qual_encoding`
+    pub virtual_offset: Option<u64>,
+}
+
+/// the progress of reading a Reader or Readers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// the number of bytes read from files, i.e. compressed bytes for gzipped files
+    pub bytes_read: u64,
+    /// the total size of files, 0 if unknown, e.g. for stdin
+    pub total_bytes: u64,
+    /// the number of uncompressed bytes parsed
+    pub uncompressed_bytes: u64,
+    /// the number of records parsed
+    pub num_records: u64,
+}
+
+impl Progress {
+    /// get the fraction of bytes read, `None` if the total size is unknown
+    pub fn fraction(&self) -> Option<f64> {
+        if self.total_bytes == 0 {
+            None
+        } else {
+            Some((self.bytes_read as f64 / self.total_bytes as f64).min(1.0))
+        }
+    }
+}
+
+impl std::ops::Add for Progress {
+    type Output = Progress;
+
+    fn add(self, other: Progress) -> Progress {
+        Progress {
+            bytes_read: self.bytes_read + other.bytes_read,
+            total_bytes: self.total_bytes + other.total_bytes,
+            uncompressed_bytes: self.uncompressed_bytes + other.uncompressed_bytes,
+            num_records: self.num_records + other.num_records,
+        }
+    }
+}
+
+// a reader of a file, which shares its position in the file
+pub(crate) struct FileInput<R> {
+    inner: R,
+    position: Rc<Cell<u64>>,
+}
+
+impl<R> FileInput<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        FileInput {
+            inner,
+            position: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub(crate) fn position(&self) -> Rc<Cell<u64>> {
+        Rc::clone(&self.position)
+    }
+}
+
+impl<R: Read> Read for FileInput<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position.set(self.position.get() + n as u64);
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for FileInput<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos)?;
+        self.position.set(position);
+        Ok(position)
+    }
+}
+
+// the input of a Reader, which counts the consumed bytes
+struct Input<'a> {
+    inner: Box<dyn io::BufRead + 'a>,
+    // the number of consumed bytes
+    offset: u64,
+    // the virtual offset of a BGZF input
+    virtual_offset: Option<Rc<Cell<u64>>>,
+    // the offset where the bytes put back end, before which virtual offsets are unknown
+    pushback_end: u64,
+}
+
+impl Input<'_> {
+    fn virtual_offset(&self) -> Option<u64> {
+        match &self.virtual_offset {
+            Some(offset) if self.offset >= self.pushback_end => Some(offset.get()),
+            _ => None,
+        }
+    }
+
+    // put back bytes read from the inner reader, which will be read again
+    fn put_back(&mut self, bytes: Vec<u8>) {
+        self.pushback_end = self.offset + bytes.len() as u64;
+        let inner = mem::replace(&mut self.inner, Box::new(io::empty()));
+        self.inner = Box::new(Cursor::new(bytes).chain(inner));
+    }
+}
+
+impl Read for Input<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl BufRead for Input<'_> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.offset += amt as u64;
+    }
+
+    // read lines by the inner reader, whose buffer is used without another layer
+    fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let n = self.inner.read_until(byte, buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+// a reader which records the consumed bytes of another reader
+struct Recorder<'r, R: ?Sized> {
+    inner: &'r mut R,
+    recorded: &'r mut Vec<u8>,
+}
+
+impl<R: BufRead + ?Sized> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.recorded.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead + ?Sized> BufRead for Recorder<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // the bytes are still in the buffer
+        if let Ok(buf) = self.inner.fill_buf() {
+            self.recorded.extend_from_slice(&buf[..amt.min(buf.len())]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
 /// a reader with shared buffer
 pub struct Reader<'a> {
-    reader: Box<dyn io::BufRead + 'a>,
+    reader: Input<'a>,
+    // the position in the file and the file size
+    file: Option<(Rc<Cell<u64>>, u64)>,
+    // the number of records read
+    num_records: u64,
     data: Vec<u8>,
+    single_line: bool,
+    // whether the first line has been checked for `\r` line endings
+    line_ending_checked: bool,
+    // the chunk of a fasta sequence being streamed
+    chunk: Vec<u8>,
+    stream: Option<Stream>,
+    // the bytes of the input borrowed by the last lines of `FastxLines::next_lines`,
+    // which are consumed by the next read
+    pending: usize,
+}
+
+// the sequence of a record being streamed in chunks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    // the sequence of a fasta record, which is read from the input
+    Fasta,
+    // the rest of the sequence of a fastq record, which is in data
+    Fastq { pos: usize, end: usize },
+}
+
+// the part of a multi-line fastq record being read
+#[derive(PartialEq, Eq)]
+enum FastqState {
+    Seq,
+    Qual,
 }
 
 impl<'a> Reader<'a> {
     // Create a new Reader
     pub(crate) fn new(r: Box<dyn io::BufRead + 'a>) -> Self {
         Reader {
-            reader: r,
+            reader: Input {
+                inner: r,
+                offset: 0,
+                virtual_offset: None,
+                pushback_end: 0,
+            },
+            file: None,
+            num_records: 0,
             data: Vec::with_capacity(1024),
+            single_line: false,
+            line_ending_checked: false,
+            chunk: Vec::new(),
+            stream: None,
+            pending: 0,
+        }
+    }
+
+    // Create a new Reader of a BGZF input, which knows the virtual offsets of records
+    pub(crate) fn with_bgzf<R: Read + 'a>(r: BgzfReader<R>) -> Self {
+        let virtual_offset = r.shared_offset();
+        let mut reader = Reader::new(Box::new(r));
+        reader.reader.virtual_offset = Some(virtual_offset);
+        reader
+    }
+
+    // Set the position of a Reader opened at a checkpoint
+    pub(crate) fn set_checkpoint(&mut self, checkpoint: &Checkpoint) {
+        self.reader.offset = checkpoint.offset;
+        self.num_records = checkpoint.record_index;
+    }
+
+    // Mark a Reader of a file before a checkpoint as read to the end, and close the file
+    pub(crate) fn set_finished(&mut self) {
+        self.reader.inner = Box::new(io::empty());
+        if let Some((position, size)) = &self.file {
+            position.set(*size);
+        }
+    }
+
+    /// assume that every sequence and quality is in a single line, which skips the
+    /// scanning for multi-line records, default is false.
+    pub fn set_single_line(&mut self, single_line: bool) {
+        self.single_line = single_line;
+    }
+
+    // Set the position in the file and the file size of a Reader
+    pub(crate) fn set_file(&mut self, position: Rc<Cell<u64>>, size: u64) {
+        self.file = Some((position, size));
+    }
+
+    /// get the number of records read
+    pub fn num_records(&self) -> u64 {
+        self.num_records
+    }
+
+    /// get the progress of reading, the bytes read from the file are only known for
+    /// a Reader of a path.
+    pub fn progress(&self) -> Progress {
+        let (bytes_read, total_bytes) = self
+            .file
+            .as_ref()
+            .map_or((0, 0), |(position, size)| (position.get(), *size));
+        Progress {
+            bytes_read,
+            total_bytes,
+            uncompressed_bytes: self.reader.offset,
+            num_records: self.num_records,
+        }
+    }
+
+    /// capture the position of the next record, return an error if the sequence of a
+    /// record read by `iter_record_chunks` is not read to the end.
+    pub fn checkpoint(&self) -> io::Result<Checkpoint> {
+        if self.stream.is_some() {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "cannot capture a checkpoint inside a record",
+            ));
         }
+        Ok(Checkpoint {
+            file_index: 0,
+            record_index: self.num_records,
+            offset: self.reader.offset,
+            virtual_offset: self.reader.virtual_offset(),
+        })
     }
 
     // Check if this reader has any data left to be read.
@@ -176,10 +833,11 @@ impl<'a> Reader<'a> {
     }
 
     // Read all non-newline bytes into data until the newline byte or EOF is reached,
-    // the newline byte (if found) will not be appended to data.
+    // the newline byte (`\n` or `\r\n`, if found) will not be appended to data.
     fn read_line(&mut self, skip_blank_line: bool) -> Result<usize> {
         let delim = b'\n';
         loop {
+            let start = self.data.len();
             let mut n = self.reader.read_until(delim, &mut self.data)?;
             // reached EOF
             if n == 0 {
@@ -190,6 +848,20 @@ impl<'a> Reader<'a> {
                 self.data.pop();
                 n -= 1;
             }
+            if n != 0 && self.data.last() == Some(&b'\r') {
+                self.data.pop();
+                n -= 1;
+            }
+            // a `\r` inside the first line is an old Mac line ending, the whole file is
+            // a single line then
+            if n != 0 && !self.line_ending_checked {
+                self.line_ending_checked = true;
+                if memchr::memchr(b'\r', &self.data[start..]).is_some() {
+                    return Err(ParseError::InvalidLineEnding(
+                        String::from_utf8_lossy(&self.data[start..]).into_owned(),
+                    ));
+                }
+            }
             if n != 0 || !skip_blank_line {
                 return Ok(n);
             }
@@ -218,6 +890,11 @@ impl<'a> Reader<'a> {
                             if available[i] == delim {
                                 break (true, i);
                             }
+                            // drop the `\r` of a `\r\n`, which may be in the previous buffer
+                            if read > 0 && self.data.last() == Some(&b'\r') {
+                                self.data.pop();
+                                read -= 1;
+                            }
                         }
                         None => {
                             self.data.extend_from_slice(&available[s..]);
@@ -228,16 +905,38 @@ impl<'a> Reader<'a> {
                 }
             };
             self.reader.consume(used);
+            if used == 0 && read > 0 && self.data.last() == Some(&b'\r') {
+                // a `\r` at the end of file without `\n`
+                self.data.pop();
+                read -= 1;
+            }
             if done || used == 0 {
                 return Ok(read);
             }
         }
     }
 
-    // Read the exact number of non-newline bytes into data.
-    fn read_exact(&mut self, len: usize) -> Result<usize> {
-        let mut read = 0;
-        loop {
+    // Find the lines of a fasta sequence at the start of the buffer, which are consumed by
+    // the next read. Return their length, or 0 at the end of the sequence.
+    fn read_fasta_lines(&mut self) -> Result<usize> {
+        self.reader.consume(mem::take(&mut self.pending));
+        let available = loop {
+            match self.reader.fill_buf() {
+                Ok(n) => break n,
+                Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(ParseError::Io(e)),
+            };
+        };
+        self.pending = memchr::memchr(b'>', available).unwrap_or(available.len());
+        Ok(self.pending)
+    }
+
+    // Read at most `max` bytes of a fasta sequence into chunk after its last `keep` bytes,
+    // newlines are skipped. Return false if the end of the sequence (the next `>` or EOF)
+    // is reached.
+    fn read_fasta_chunk(&mut self, max: usize, keep: usize) -> Result<bool> {
+        self.chunk.drain(..self.chunk.len() - keep);
+        while self.chunk.len() < max {
             let (done, used) = {
                 let available = match self.reader.fill_buf() {
                     Ok(n) => n,
@@ -245,45 +944,178 @@ impl<'a> Reader<'a> {
                     Err(e) => return Err(ParseError::Io(e)),
                 };
                 let mut s = 0;
-                let mut mch = memchr::memchr_iter(b'\n', available);
+                let mut mch = memchr::memchr3_iter(b'>', b'\n', b'\r', available);
                 loop {
-                    match mch.next() {
-                        Some(i) => {
-                            if read + i - s >= len {
-                                let e = len - read + s;
-                                self.data.extend_from_slice(&available[s..e]);
-                                read = len;
-                                break (true, e);
-                            } else {
-                                self.data.extend_from_slice(&available[s..i]);
-                                read += i - s;
-                            }
-                            s = i + 1;
-                        }
-                        None => {
-                            if available.len() - s + read >= len {
-                                let e = len - read + s;
-                                self.data.extend_from_slice(&available[s..e]);
-                                read = len;
-                                break (true, e);
-                            } else {
-                                self.data.extend_from_slice(&available[s..]);
-                                read += available.len() - s;
-                                break (false, available.len());
-                            }
-                        }
+                    let i = mch.next().unwrap_or(available.len());
+                    let take = (i - s).min(max - self.chunk.len());
+                    self.chunk.extend_from_slice(&available[s..s + take]);
+                    if s + take < i {
+                        // the chunk is full
+                        break (false, s + take);
+                    } else if i == available.len() {
+                        break (available.is_empty(), i);
+                    } else if available[i] == b'>' {
+                        break (true, i);
                     }
+                    s = i + 1;
                 }
             };
             self.reader.consume(used);
-            if done || used == 0 {
-                return Ok(read);
+            if done {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Read the sequence, separator and quality lines of a multi-line fastq record into data,
+    // quality lines are read until the quality is as long as the sequence, so quality lines
+    // starting with `@` or `+` are never taken as a new record or separator.
+    // Return the end positions of the sequence, separator and quality in data.
+    fn read_fastq_lines(&mut self) -> Result<(usize, usize, usize)> {
+        let des = self.data.len();
+        let mut state = FastqState::Seq;
+        let mut seq = des;
+        let mut sep = des;
+        loop {
+            if state == FastqState::Qual && self.data.len() - sep >= seq - des {
+                break;
+            }
+            let start = self.data.len();
+            if self.read_line(true)? == 0 {
+                // reached EOF
+                break;
+            }
+            match state {
+                FastqState::Seq if self.data[start] == b'+' => {
+                    seq = start;
+                    sep = self.data.len();
+                    state = FastqState::Qual;
+                }
+                FastqState::Seq => seq = self.data.len(),
+                FastqState::Qual => {}
+            }
+        }
+        if state == FastqState::Seq {
+            sep = seq;
+        }
+        Ok((seq, sep, self.data.len()))
+    }
+
+    /// detect the quality encoding from the first `n` records, these records are
+    /// not consumed and will still be returned by `iter_record`. Return `None`
+    /// if no quality scores were found, e.g. for a fasta file.
+    pub fn detect_qual_encoding(&mut self, n: usize) -> Result<Option<QualEncoding>> {
+        self.skip_stream()?;
+        let mut min = u8::MAX;
+        let mut max = u8::MIN;
+        let mut recorded = Vec::new();
+        {
+            // read the records by another Reader, which records the bytes to put them back
+            let recorder = Recorder {
+                inner: &mut *self.reader.inner,
+                recorded: &mut recorded,
+            };
+            let mut reader = Reader::new(Box::new(recorder));
+            reader.single_line = self.single_line;
+            for _ in 0..n {
+                match reader.iter_record()? {
+                    Some(record) => {
+                        for &q in record.qual().as_bytes() {
+                            min = min.min(q);
+                            max = max.max(q);
+                        }
+                    }
+                    None => break,
+                }
             }
         }
+        self.reader.put_back(recorded);
+        Ok(if min <= max {
+            Some(QualEncoding::guess(min, max))
+        } else {
+            None
+        })
     }
 
     /// iterate over a record from this Reader
-    pub fn iter_record(&mut self) -> Result<Option<Fastx>> {
+    pub fn iter_record(&mut self) -> Result<Option<Fastx<'_>>> {
+        match self.read_head()? {
+            Some((head, des)) => self.read_body(head, des).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// iterate over a record from this Reader, whose sequence is read as lines by
+    /// `FastxLines::next_lines` without being copied, which saves the concatenation of long
+    /// wrapped fasta records. The rest of the sequence is skipped by the next call.
+    pub fn iter_record_lines(&mut self) -> Result<Option<FastxLines<'_, 'a>>> {
+        let (head, des) = match self.read_head()? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        self.start_stream(head, des)?;
+        Ok(Some(FastxLines {
+            reader: self,
+            head,
+            des,
+            has_bases: false,
+        }))
+    }
+
+    /// iterate over a record from this Reader, whose sequence is read in chunks of at most
+    /// `chunk_size` bytes by `ChunkedRecord::next_chunk`, so a fasta record of any length
+    /// is read in a bounded memory. The rest of the sequence is skipped by the next call.
+    pub fn iter_record_chunks(
+        &mut self,
+        chunk_size: usize,
+    ) -> Result<Option<ChunkedRecord<'_, 'a>>> {
+        let (head, des) = match self.read_head()? {
+            Some(head) => head,
+            None => return Ok(None),
+        };
+        self.start_stream(head, des)?;
+        Ok(Some(ChunkedRecord {
+            reader: self,
+            head,
+            des,
+            chunk_size: chunk_size.max(1),
+            len: 0,
+            split: 0,
+        }))
+    }
+
+    // Start streaming the sequence of a record after its head line, a fastq record is read
+    // at once
+    fn start_stream(&mut self, head: usize, des: usize) -> Result<()> {
+        if self.data[0] == b'>' {
+            if head == 1 {
+                return Err(ParseError::InvalidFasta(String::new()));
+            }
+            self.stream = Some(Stream::Fasta);
+        } else {
+            let fastq = self.read_body(head, des)?;
+            self.stream = Some(Stream::Fastq {
+                pos: fastq._des,
+                end: fastq._seq,
+            });
+        }
+        Ok(())
+    }
+
+    // Skip the rest of the sequence of a record being streamed
+    fn skip_stream(&mut self) -> Result<()> {
+        self.reader.consume(mem::take(&mut self.pending));
+        if self.stream.take() == Some(Stream::Fasta) {
+            while self.read_fasta_chunk(1 << 20, 0)? {}
+        }
+        Ok(())
+    }
+
+    // Read the head line of a record into data, the rest of a record being streamed is
+    // skipped first. Return the end positions of the head and description in data.
+    fn read_head(&mut self) -> Result<Option<(usize, usize)>> {
+        self.skip_stream()?;
         // clean the last record
         self.data.clear();
         // read sequence head
@@ -297,23 +1129,33 @@ impl<'a> Reader<'a> {
                 String::from_utf8(self.data.to_owned()).unwrap(),
             ));
         }
+        self.num_records += 1;
 
         let head = self
             .data
             .iter()
             .position(|&x| char::is_whitespace(x as char))
-            .map_or_else(|| des, |x| x);
+            .unwrap_or(des);
+        Ok(Some((head, des)))
+    }
+
+    // Read the rest of a record after its head line
+    fn read_body(&mut self, head: usize, des: usize) -> Result<Fastx<'_>> {
         let mut seq = des;
         let mut sep = seq;
         let mut qual = sep;
 
         let is_fasta = self.data[0] == b'>';
-        if is_fasta {
+        if self.single_line {
+            seq += self.read_line(false)?;
+            if !is_fasta {
+                sep = seq + self.read_line(false)?;
+                qual = sep + self.read_line(false)?;
+            }
+        } else if is_fasta {
             seq += self.read_until(b'>')?;
         } else {
-            seq += self.read_until(b'+')?;
-            sep = seq + self.read_line(true)?;
-            qual = sep + self.read_exact(seq - des)?;
+            (seq, sep, qual) = self.read_fastq_lines()?;
         }
 
         if !self.has_data_left()? && (head == 1 || seq == des || (!is_fasta && (sep == seq || qual == sep))){
@@ -337,14 +1179,16 @@ impl<'a> Reader<'a> {
         } else if !(is_fasta || fastx.validate_fastq()) {
             return Err(ParseError::InvalidFastq(fastx.head().to_string()));
         }
-        Ok(Some(fastx))
+        Ok(fastx)
     }
 }
 
 /// multiple readers for a fofn file
 pub struct Readers<'a> {
-    index: usize,
+    pub(crate) index: usize,
     pub(crate) readers: Vec<Reader<'a>>,
+    // the paths of readers
+    pub(crate) paths: Vec<PathBuf>,
 }
 
 impl<'a> Default for Readers<'a> {
@@ -359,11 +1203,55 @@ impl<'a> Readers<'a> {
         Readers {
             index: 0,
             readers: Vec::new(),
+            paths: Vec::new(),
+        }
+    }
+
+    /// get the progress of reading all Readers
+    pub(crate) fn progress(&self) -> Progress {
+        self.readers
+            .iter()
+            .map(Reader::progress)
+            .fold(Progress::default(), |a, b| a + b)
+    }
+
+    /// capture the position of the next record of the current Reader
+    pub(crate) fn checkpoint(&self) -> io::Result<Checkpoint> {
+        let num_records = self.readers.iter().map(Reader::num_records).sum();
+        Ok(match self.readers.get(self.index) {
+            Some(reader) => Checkpoint {
+                file_index: self.index,
+                record_index: num_records,
+                ..reader.checkpoint()?
+            },
+            None => Checkpoint {
+                file_index: self.index,
+                record_index: num_records,
+                ..Default::default()
+            },
+        })
+    }
+
+    /// assume that every sequence and quality is in a single line for all Readers
+    pub(crate) fn set_single_line(&mut self, single_line: bool) {
+        self.readers
+            .iter_mut()
+            .for_each(|reader| reader.set_single_line(single_line));
+    }
+
+    /// detect the quality encoding from the first `n` records of the current Reader
+    pub(crate) fn detect_qual_encoding(&mut self, n: usize) -> Result<Option<QualEncoding>> {
+        for idx in self.index..self.readers.len() {
+            if self.readers[idx].has_data_left()? {
+                return self.readers[idx].detect_qual_encoding(n);
+            }
+            self.index += 1;
         }
+        Ok(None)
     }
 
     /// iterate over a record from this Readers
-    pub(crate) fn iter_record(&mut self) -> Result<Option<Fastx>> {
+    pub(crate) fn iter_record(&mut self) -> Result<Option<Fastx<'_>>> {
         for idx in self.index..self.readers.len() {
             if self.readers[idx].has_data_left()? {
                 return self.readers[idx].iter_record();
@@ -372,4 +1260,29 @@ impl<'a> Readers<'a> {
         }
         Ok(None)
     }
+
+    /// iterate over a record from this Readers, whose sequence is read in chunks
+    pub(crate) fn iter_record_chunks(
+        &mut self,
+        chunk_size: usize,
+    ) -> Result<Option<ChunkedRecord<'_, 'a>>> {
+        for idx in self.index..self.readers.len() {
+            if self.readers[idx].has_data_left()? {
+                return self.readers[idx].iter_record_chunks(chunk_size);
+            }
+            self.index += 1;
+        }
+        Ok(None)
+    }
+
+    /// iterate over a record from this Readers, whose sequence is read line by line
+    pub(crate) fn iter_record_lines(&mut self) -> Result<Option<FastxLines<'_, 'a>>> {
+        for idx in self.index..self.readers.len() {
+            if self.readers[idx].has_data_left()? {
+                return self.readers[idx].iter_record_lines();
+            }
+            self.index += 1;
+        }
+        Ok(None)
+    }
 }
@@ -0,0 +1,228 @@
+//! Extraction of sub-sequences by regions of BED or GFF files, similar to `seqkit subseq --bed`
+//!
+//! Regions are kept in memory by sequence name and sequences are streamed, so no index of
+//! the fasta file is needed. Sub-sequences of the `-` strand are reverse complemented, and
+//! are written as fasta records named by their regions, e.g. `chr1_11-20:-` for the 1-based
+//! inclusive region `11-20` of `chr1`, followed by the name of the region if any.
+//!
+//! ```
+//! use kseq::filter::Strand;
+//! use kseq::region::{Region, Regions};
+//!
+//! let mut regions = Regions::new();
+//! regions.add(Region::new("chr1", 2, 6, Some(Strand::Reverse)));
+//! let mut records = kseq::parse_reader(&b">chr1\nAACCGGTT\n"[..]).unwrap();
+//! let record = records.iter_record().unwrap().unwrap();
+//! let subseqs = regions.extract(&record);
+//! assert_eq!((subseqs[0].head.as_str(), subseqs[0].seq.as_str()), ("chr1_3-6:-", "CCGG"));
+//! ```
+
+use crate::filter::Strand;
+use crate::record::{FastxRecord, OwnedFastx, Result as ParseResult};
+use crate::seq::revcomp_str;
+use crate::writer::Writer;
+use crate::Paths;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// a region of a sequence, the coordinates are 0-based and half-open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    /// the name of the sequence
+    pub chrom: String,
+    /// the start of the region
+    pub start: usize,
+    /// the end of the region
+    pub end: usize,
+    /// the name of the region
+    pub name: Option<String>,
+    /// the strand of the region, `None` if unknown
+    pub strand: Option<Strand>,
+}
+
+impl Region {
+    /// create a new Region without a name
+    pub fn new(chrom: &str, start: usize, end: usize, strand: Option<Strand>) -> Self {
+        Region {
+            chrom: chrom.to_string(),
+            start,
+            end,
+            name: None,
+            strand,
+        }
+    }
+
+    /// get the sub-sequence of a region as a fasta record, the region is clipped to the end
+    /// of the sequence, return `None` if the region is out of the sequence or empty, or if
+    /// the sub-sequence is not ASCII.
+    pub fn extract(&self, seq: &str) -> Option<OwnedFastx> {
+        let end = self.end.min(seq.len());
+        if self.start >= end {
+            return None;
+        }
+        let seq = seq.get(self.start..end).filter(|seq| seq.is_ascii())?;
+        let (seq, strand) = match self.strand {
+            Some(Strand::Reverse) => (revcomp_str(seq), '-'),
+            Some(Strand::Forward) => (seq.to_string(), '+'),
+            None => (seq.to_string(), '.'),
+        };
+        Some(OwnedFastx {
+            head: format!("{}_{}-{}:{}", self.chrom, self.start + 1, end, strand),
+            des: self
+                .name
+                .as_ref()
+                .map_or_else(String::new, |name| format!(" {}", name)),
+            seq,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_strand(strand: &str) -> Option<Strand> {
+    match strand {
+        "+" => Some(Strand::Forward),
+        "-" => Some(Strand::Reverse),
+        _ => None,
+    }
+}
+
+fn invalid_line(i: usize, line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid region line {}: {}", i + 1, line),
+    )
+}
+
+/// a set of regions grouped by sequence names
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Regions {
+    regions: HashMap<String, Vec<Region>>,
+    len: usize,
+}
+
+impl Regions {
+    /// create an empty Regions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// load regions from a BED file, with at least 3 columns `chrom start end`, and the
+    /// optional columns `name score strand`, separated by tabs or spaces. Empty lines and `#`,
+    /// `track` or `browser` lines are skipped.
+    pub fn from_bed<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut regions = Regions::new();
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim_end();
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.first() {
+                None | Some(&"track") | Some(&"browser") => continue,
+                Some(chrom) if chrom.starts_with('#') => continue,
+                _ => {}
+            }
+            if fields.len() < 3 {
+                return Err(invalid_line(i, line));
+            }
+            let start: usize = fields[1].parse().map_err(|_| invalid_line(i, line))?;
+            let end: usize = fields[2].parse().map_err(|_| invalid_line(i, line))?;
+            if start > end {
+                return Err(invalid_line(i, line));
+            }
+            regions.add(Region {
+                name: fields
+                    .get(3)
+                    .filter(|name| !name.is_empty() && **name != ".")
+                    .map(|name| name.to_string()),
+                strand: fields.get(5).and_then(|strand| parse_strand(strand)),
+                ..Region::new(fields[0], start, end, None)
+            });
+        }
+        Ok(regions)
+    }
+
+    /// load regions from a GFF3 file, only features of `feature_type` (the third column) are
+    /// loaded if it is given. The name of a region is the `Name` or `ID` attribute. Empty lines
+    /// and `#` lines are skipped, and sequences after `##FASTA` are ignored.
+    pub fn from_gff<P: AsRef<Path>>(path: P, feature_type: Option<&str>) -> io::Result<Self> {
+        let mut regions = Regions::new();
+        for (i, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim_end();
+            if line.starts_with("##FASTA") {
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 9 {
+                return Err(invalid_line(i, line));
+            }
+            if feature_type.is_some_and(|t| t != fields[2]) {
+                continue;
+            }
+            // 1-based and inclusive
+            let start: usize = fields[3].parse().map_err(|_| invalid_line(i, line))?;
+            let end: usize = fields[4].parse().map_err(|_| invalid_line(i, line))?;
+            if start == 0 || start > end {
+                return Err(invalid_line(i, line));
+            }
+            let attribute = |key: &str| {
+                fields[8]
+                    .split(';')
+                    .find_map(|attr| attr.trim().strip_prefix(key)?.strip_prefix('='))
+                    .map(str::to_string)
+            };
+            regions.add(Region {
+                name: attribute("Name").or_else(|| attribute("ID")),
+                ..Region::new(fields[0], start - 1, end, parse_strand(fields[6]))
+            });
+        }
+        Ok(regions)
+    }
+
+    /// add a region
+    pub fn add(&mut self, region: Region) {
+        self.regions
+            .entry(region.chrom.clone())
+            .or_default()
+            .push(region);
+        self.len += 1;
+    }
+
+    /// get the number of regions
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// check whether there is no region
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// get the sub-sequences of the regions of a record, in the order the regions are added
+    pub fn extract<R: FastxRecord>(&self, record: &R) -> Vec<OwnedFastx> {
+        self.regions
+            .get(record.head())
+            .map_or_else(Vec::new, |regions| {
+                regions
+                    .iter()
+                    .filter_map(|region| region.extract(record.seq()))
+                    .collect()
+            })
+    }
+
+    /// write the sub-sequences of the regions of all records of a Reader or Readers to a
+    /// Writer, return the number of sub-sequences written.
+    pub fn run<W: Write>(&self, paths: &mut Paths, writer: &mut Writer<W>) -> ParseResult<u64> {
+        let mut n = 0;
+        while let Some(record) = paths.iter_record()? {
+            for subseq in self.extract(&record) {
+                writer.write_fasta(&subseq)?;
+                n += 1;
+            }
+        }
+        writer.flush()?;
+        Ok(n)
+    }
+}
@@ -0,0 +1,247 @@
+//! Re-pairing of mates from paired files whose records are out of sync
+//!
+//! Mates are matched by their names without a `/1` or `/2` suffix, see [`mate_name`], records
+//! of the two files may be in any order. Matched mates are written to two paired outputs in
+//! the same order, and records without a mate are written to two orphan outputs.
+//!
+//! The in-memory mode keeps unmatched records in memory until their mates are found, which is
+//! fast if the two files are roughly in the same order. The disk-backed mode sorts both files
+//! by name with an external merge sort, then joins them, in a bounded memory.
+//!
+//! ```
+//! use kseq::repair::Repairer;
+//! use kseq::writer::Writer;
+//!
+//! let mut r1 = kseq::parse_reader(&b">a/1\nAC\n>b/1\nGG\n>c/1\nTT\n"[..]).unwrap();
+//! let mut r2 = kseq::parse_reader(&b">c/2\nAA\n>a/2\nCC\n"[..]).unwrap();
+//! let (mut out1, mut out2) = (Writer::new(Vec::new()), Writer::new(Vec::new()));
+//! let (mut orphans1, mut orphans2) = (Writer::new(Vec::new()), Writer::new(Vec::new()));
+//! let stats = Repairer::in_memory()
+//!     .run(&mut r1, &mut r2, &mut out1, &mut out2, &mut orphans1, &mut orphans2)
+//!     .unwrap();
+//! assert_eq!((stats.pairs, stats.orphans1, stats.orphans2), (2, 1, 0));
+//! ```
+
+use crate::record::{FastxRecord, OwnedFastx, Result as ParseResult};
+use crate::sort::{SortKey, Sorter};
+use crate::writer::Writer;
+use crate::Paths;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// get the name of mates from the name of a record, i.e. the name without a `/1` or `/2` suffix
+pub fn mate_name(head: &str) -> &str {
+    head.strip_suffix("/1")
+        .or_else(|| head.strip_suffix("/2"))
+        .unwrap_or(head)
+}
+
+/// the numbers of records written by a [`Repairer`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairStats {
+    /// the number of pairs of mates
+    pub pairs: u64,
+    /// the number of records of the first file without a mate
+    pub orphans1: u64,
+    /// the number of records of the second file without a mate
+    pub orphans2: u64,
+}
+
+// the outputs of a Repairer
+struct Outputs<'a, W: Write> {
+    out1: &'a mut Writer<W>,
+    out2: &'a mut Writer<W>,
+    orphans1: &'a mut Writer<W>,
+    orphans2: &'a mut Writer<W>,
+    stats: RepairStats,
+}
+
+impl<W: Write> Outputs<'_, W> {
+    fn pair<R: FastxRecord>(&mut self, r1: &R, r2: &R) -> ParseResult<()> {
+        self.out1.write_record(r1)?;
+        self.out2.write_record(r2)?;
+        self.stats.pairs += 1;
+        Ok(())
+    }
+
+    fn orphan1<R: FastxRecord>(&mut self, record: &R) -> ParseResult<()> {
+        self.orphans1.write_record(record)?;
+        self.stats.orphans1 += 1;
+        Ok(())
+    }
+
+    fn orphan2<R: FastxRecord>(&mut self, record: &R) -> ParseResult<()> {
+        self.orphans2.write_record(record)?;
+        self.stats.orphans2 += 1;
+        Ok(())
+    }
+
+    fn finish(self) -> ParseResult<RepairStats> {
+        self.out1.flush()?;
+        self.out2.flush()?;
+        self.orphans1.flush()?;
+        self.orphans2.flush()?;
+        Ok(self.stats)
+    }
+}
+
+// the unmatched records of a file, with their indexes to write orphans in the input order
+type Pending = HashMap<String, (u64, OwnedFastx)>;
+
+/// a re-pairer of mates from two files
+#[derive(Debug, Clone)]
+pub struct Repairer {
+    // the sorter of the disk-backed mode
+    sorter: Option<Sorter>,
+}
+
+impl Repairer {
+    /// create a Repairer keeping unmatched records in memory
+    pub fn in_memory() -> Self {
+        Repairer { sorter: None }
+    }
+
+    /// create a Repairer sorting records by name in temporary files, with the approximate
+    /// memory (in bytes) of records kept in memory for each file.
+    pub fn on_disk(memory: usize) -> Self {
+        let mut sorter = Sorter::new(SortKey::MateName);
+        sorter.set_memory(memory);
+        Repairer {
+            sorter: Some(sorter),
+        }
+    }
+
+    /// set the directory of temporary files of the disk-backed mode
+    pub fn set_tmp_dir<P: Into<PathBuf>>(&mut self, tmp_dir: P) {
+        if let Some(sorter) = self.sorter.as_mut() {
+            sorter.set_tmp_dir(tmp_dir);
+        }
+    }
+
+    /// re-pair the records of two Reader or Readers, write matched mates to `out1` and
+    /// `out2` and records without a mate to `orphans1` and `orphans2`. If a name occurs
+    /// several times in a file, each record is matched with at most one mate.
+    ///
+    /// Pairs are written in the order they are found for the in-memory mode and in the order
+    /// of names for the disk-backed mode. For the in-memory mode, records with a duplicated
+    /// name are orphans once replaced, and the other orphans are written at the end in the
+    /// input order.
+    pub fn run<W: Write>(
+        &self,
+        paths1: &mut Paths,
+        paths2: &mut Paths,
+        out1: &mut Writer<W>,
+        out2: &mut Writer<W>,
+        orphans1: &mut Writer<W>,
+        orphans2: &mut Writer<W>,
+    ) -> ParseResult<RepairStats> {
+        let mut outputs = Outputs {
+            out1,
+            out2,
+            orphans1,
+            orphans2,
+            stats: RepairStats::default(),
+        };
+        match &self.sorter {
+            Some(sorter) => join(sorter, paths1, paths2, &mut outputs)?,
+            None => match_in_memory(paths1, paths2, &mut outputs)?,
+        }
+        outputs.finish()
+    }
+}
+
+// read the two files in turn, and match each record with the unmatched records of the other
+fn match_in_memory<W: Write>(
+    paths1: &mut Paths,
+    paths2: &mut Paths,
+    outputs: &mut Outputs<W>,
+) -> ParseResult<()> {
+    let (mut pending1, mut pending2) = (Pending::new(), Pending::new());
+    let (mut done1, mut done2) = (false, false);
+    let mut i = 0;
+    while !(done1 && done2) {
+        if !done1 {
+            match paths1.iter_record()? {
+                Some(r1) => match pending2.remove(mate_name(r1.head())) {
+                    Some((_, r2)) => outputs.pair(&r1.to_owned_fastx(), &r2)?,
+                    None => {
+                        let r1 = r1.to_owned_fastx();
+                        let name = mate_name(&r1.head).to_string();
+                        // a duplicated name replaces the previous record, which becomes an orphan
+                        if let Some((_, old)) = pending1.insert(name, (i, r1)) {
+                            outputs.orphan1(&old)?;
+                        }
+                    }
+                },
+                None => done1 = true,
+            }
+        }
+        if !done2 {
+            match paths2.iter_record()? {
+                Some(r2) => match pending1.remove(mate_name(r2.head())) {
+                    Some((_, r1)) => outputs.pair(&r1, &r2.to_owned_fastx())?,
+                    None => {
+                        let r2 = r2.to_owned_fastx();
+                        let name = mate_name(&r2.head).to_string();
+                        // a duplicated name replaces the previous record, which becomes an orphan
+                        if let Some((_, old)) = pending2.insert(name, (i, r2)) {
+                            outputs.orphan2(&old)?;
+                        }
+                    }
+                },
+                None => done2 = true,
+            }
+        }
+        i += 1;
+    }
+    let mut orphans1: Vec<_> = pending1.into_values().collect();
+    orphans1.sort_unstable_by_key(|(i, _)| *i);
+    for (_, record) in orphans1 {
+        outputs.orphan1(&record)?;
+    }
+    let mut orphans2: Vec<_> = pending2.into_values().collect();
+    orphans2.sort_unstable_by_key(|(i, _)| *i);
+    for (_, record) in orphans2 {
+        outputs.orphan2(&record)?;
+    }
+    Ok(())
+}
+
+// sort the two files by name, and join them
+fn join<W: Write>(
+    sorter: &Sorter,
+    paths1: &mut Paths,
+    paths2: &mut Paths,
+    outputs: &mut Outputs<W>,
+) -> ParseResult<()> {
+    let mut sorted1 = sorter.sort(paths1)?;
+    let mut sorted2 = sorter.sort(paths2)?;
+    let mut r1 = sorted1.next().transpose()?;
+    let mut r2 = sorted2.next().transpose()?;
+    loop {
+        let ord = match (&r1, &r2) {
+            (Some(a), Some(b)) => mate_name(&a.head).cmp(mate_name(&b.head)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return Ok(()),
+        };
+        // safely unwrap, the records are checked above
+        match ord {
+            Ordering::Less => {
+                outputs.orphan1(r1.as_ref().unwrap())?;
+                r1 = sorted1.next().transpose()?;
+            }
+            Ordering::Greater => {
+                outputs.orphan2(r2.as_ref().unwrap())?;
+                r2 = sorted2.next().transpose()?;
+            }
+            Ordering::Equal => {
+                outputs.pair(r1.as_ref().unwrap(), r2.as_ref().unwrap())?;
+                r1 = sorted1.next().transpose()?;
+                r2 = sorted2.next().transpose()?;
+            }
+        }
+    }
+}
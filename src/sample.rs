@@ -0,0 +1,275 @@
+//! Subsampling of records, similar to `seqtk sample`
+//!
+//! Records are sampled by a fraction or to a fixed number with a seed, the same seed
+//! always selects the same records. Paired files are sampled together so mates stay
+//! together. Sampled records are returned or written in their input order.
+//!
+//! ```
+//! use kseq::sample::Sampler;
+//!
+//! let data = b">1\nA\n>2\nC\n>3\nG\n>4\nT\n";
+//! let sampler = Sampler::new(11);
+//! let records = sampler.reservoir(&mut kseq::parse_reader(&data[..]).unwrap(), 2).unwrap();
+//! assert_eq!(records.len(), 2);
+//! let again = sampler.reservoir(&mut kseq::parse_reader(&data[..]).unwrap(), 2).unwrap();
+//! assert_eq!(records, again);
+//! ```
+
+use crate::parse_path;
+use crate::record::{OwnedFastx, Result as ParseResult};
+use crate::writer::Writer;
+use crate::{each_pair, Paths};
+use std::io::Write;
+use std::path::Path;
+
+// the splitmix64 generator, which is small, fast and stable across platforms and versions
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // a uniform number in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // a uniform number in [0, n)
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+// the reservoir sampling (algorithm R) of the indices of records
+struct Reservoir {
+    rng: Rng,
+    size: usize,
+    seen: u64,
+}
+
+impl Reservoir {
+    fn new(seed: u64, size: usize) -> Self {
+        Reservoir {
+            rng: Rng(seed),
+            size,
+            seen: 0,
+        }
+    }
+
+    // offer the next record, return the slot in the reservoir it takes, if any
+    fn offer(&mut self) -> Option<usize> {
+        let i = self.seen;
+        self.seen += 1;
+        if i < self.size as u64 {
+            Some(i as usize)
+        } else {
+            let j = self.rng.below(i + 1);
+            if j < self.size as u64 {
+                Some(j as usize)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// a sampler of records with a seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sampler {
+    /// the seed of the random number generator
+    pub seed: u64,
+}
+
+impl Default for Sampler {
+    fn default() -> Self {
+        Sampler { seed: 11 }
+    }
+}
+
+impl Sampler {
+    /// create a new Sampler with a seed
+    pub fn new(seed: u64) -> Self {
+        Sampler { seed }
+    }
+
+    /// write each record with a probability of `fraction` to a Writer,
+    /// return the number of records written.
+    pub fn fraction<W: Write>(
+        &self,
+        paths: &mut Paths,
+        fraction: f64,
+        writer: &mut Writer<W>,
+    ) -> ParseResult<u64> {
+        let mut rng = Rng(self.seed);
+        let mut n = 0;
+        while let Some(record) = paths.iter_record()? {
+            if rng.next_f64() < fraction {
+                writer.write_record(&record)?;
+                n += 1;
+            }
+        }
+        writer.flush()?;
+        Ok(n)
+    }
+
+    /// write each pair of records with a probability of `fraction` to two Writers,
+    /// return the number of pairs written.
+    pub fn fraction_paired<W1: Write, W2: Write>(
+        &self,
+        paths1: &mut Paths,
+        paths2: &mut Paths,
+        fraction: f64,
+        writer1: &mut Writer<W1>,
+        writer2: &mut Writer<W2>,
+    ) -> ParseResult<u64> {
+        let mut rng = Rng(self.seed);
+        let mut n = 0;
+        each_pair(paths1, paths2, |r1, r2| {
+            if rng.next_f64() < fraction {
+                writer1.write_record(r1)?;
+                writer2.write_record(r2)?;
+                n += 1;
+            }
+            Ok(())
+        })?;
+        writer1.flush()?;
+        writer2.flush()?;
+        Ok(n)
+    }
+
+    /// sample `n` records (or all records if there are fewer) into memory
+    pub fn reservoir(&self, paths: &mut Paths, n: usize) -> ParseResult<Vec<OwnedFastx>> {
+        let mut reservoir = Reservoir::new(self.seed, n);
+        let mut sampled: Vec<(u64, OwnedFastx)> = Vec::new();
+        while let Some(record) = paths.iter_record()? {
+            let index = reservoir.seen;
+            match reservoir.offer() {
+                Some(slot) if slot == sampled.len() => {
+                    sampled.push((index, record.to_owned_fastx()))
+                }
+                Some(slot) => sampled[slot] = (index, record.to_owned_fastx()),
+                None => {}
+            }
+        }
+        sampled.sort_unstable_by_key(|&(index, _)| index);
+        Ok(sampled.into_iter().map(|(_, record)| record).collect())
+    }
+
+    /// sample `n` pairs of records (or all pairs if there are fewer) into memory
+    pub fn reservoir_paired(
+        &self,
+        paths1: &mut Paths,
+        paths2: &mut Paths,
+        n: usize,
+    ) -> ParseResult<Vec<(OwnedFastx, OwnedFastx)>> {
+        let mut reservoir = Reservoir::new(self.seed, n);
+        let mut sampled: Vec<(u64, (OwnedFastx, OwnedFastx))> = Vec::new();
+        each_pair(paths1, paths2, |r1, r2| {
+            let index = reservoir.seen;
+            match reservoir.offer() {
+                Some(slot) if slot == sampled.len() => {
+                    sampled.push((index, (r1.into(), r2.into())))
+                }
+                Some(slot) => sampled[slot] = (index, (r1.into(), r2.into())),
+                None => {}
+            }
+            Ok(())
+        })?;
+        sampled.sort_unstable_by_key(|&(index, _)| index);
+        Ok(sampled.into_iter().map(|(_, pair)| pair).collect())
+    }
+
+    /// sample `n` records of a path in two passes and write them to a Writer, only the
+    /// indices of sampled records are kept in memory. The same records as `reservoir`
+    /// are selected for the same seed. Return the number of records written.
+    pub fn exact<P: AsRef<Path>, W: Write>(
+        &self,
+        path: P,
+        n: usize,
+        writer: &mut Writer<W>,
+    ) -> ParseResult<u64> {
+        let path = path.as_ref();
+        let indices = self.select(&mut parse_path(path)?, None, n)?;
+        let mut paths = parse_path(path)?;
+        let mut written = 0;
+        let mut index = 0;
+        while let Some(record) = paths.iter_record()? {
+            if indices.get(written) == Some(&index) {
+                writer.write_record(&record)?;
+                written += 1;
+            }
+            index += 1;
+        }
+        writer.flush()?;
+        Ok(written as u64)
+    }
+
+    /// sample `n` pairs of records of two paired paths in two passes and write them to
+    /// two Writers, return the number of pairs written.
+    pub fn exact_paired<P: AsRef<Path>, W1: Write, W2: Write>(
+        &self,
+        path1: P,
+        path2: P,
+        n: usize,
+        writer1: &mut Writer<W1>,
+        writer2: &mut Writer<W2>,
+    ) -> ParseResult<u64> {
+        let (path1, path2) = (path1.as_ref(), path2.as_ref());
+        let indices = self.select(&mut parse_path(path1)?, Some(&mut parse_path(path2)?), n)?;
+        let mut written = 0;
+        let mut index = 0;
+        each_pair(
+            &mut parse_path(path1)?,
+            &mut parse_path(path2)?,
+            |r1, r2| {
+                if indices.get(written) == Some(&index) {
+                    writer1.write_record(r1)?;
+                    writer2.write_record(r2)?;
+                    written += 1;
+                }
+                index += 1;
+                Ok(())
+            },
+        )?;
+        writer1.flush()?;
+        writer2.flush()?;
+        Ok(written as u64)
+    }
+
+    // the first pass of exact sampling, get the sorted indices of sampled records
+    fn select(
+        &self,
+        paths1: &mut Paths,
+        paths2: Option<&mut Paths>,
+        n: usize,
+    ) -> ParseResult<Vec<u64>> {
+        let mut reservoir = Reservoir::new(self.seed, n);
+        let mut indices = Vec::new();
+        let mut offer = |reservoir: &mut Reservoir| {
+            let index = reservoir.seen;
+            match reservoir.offer() {
+                Some(slot) if slot == indices.len() => indices.push(index),
+                Some(slot) => indices[slot] = index,
+                None => {}
+            }
+        };
+        match paths2 {
+            Some(paths2) => each_pair(paths1, paths2, |_, _| {
+                offer(&mut reservoir);
+                Ok(())
+            })?,
+            None => {
+                while paths1.iter_record()?.is_some() {
+                    offer(&mut reservoir);
+                }
+            }
+        }
+        indices.sort_unstable();
+        Ok(indices)
+    }
+}
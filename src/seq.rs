@@ -0,0 +1,215 @@
+//! Sequence manipulation helpers
+//!
+//! All functions work on bytes of a sequence, e.g. `record.seq().as_bytes()` of a borrowed
+//! record, while [`OwnedFastx`] also gets in-place versions updating both sequence and quality.
+//! IUPAC codes are complemented and the case of each base is preserved. Characters other than
+//! ASCII, which may be put in the fields of an [`OwnedFastx`], are kept by reverse complements
+//! and the other in-place versions leave such sequences unchanged.
+
+use crate::record::{FastxRecord, OwnedFastx};
+use std::mem;
+
+const fn complement_table(rna: bool) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = i as u8;
+        i += 1;
+    }
+    let pairs: [(u8, u8); 13] = [
+        (b'A', if rna { b'U' } else { b'T' }),
+        (b'T', b'A'),
+        (b'U', b'A'),
+        (b'C', b'G'),
+        (b'G', b'C'),
+        (b'R', b'Y'),
+        (b'Y', b'R'),
+        (b'K', b'M'),
+        (b'M', b'K'),
+        (b'B', b'V'),
+        (b'V', b'B'),
+        (b'D', b'H'),
+        (b'H', b'D'),
+    ];
+    let mut i = 0;
+    while i < pairs.len() {
+        let (from, to) = pairs[i];
+        table[from as usize] = to;
+        table[(from + 32) as usize] = to + 32;
+        i += 1;
+    }
+    table
+}
+
+static DNA_COMPLEMENT: [u8; 256] = complement_table(false);
+static RNA_COMPLEMENT: [u8; 256] = complement_table(true);
+
+/// get the complement of a DNA base, IUPAC-aware and case-preserving
+#[inline]
+pub fn complement(base: u8) -> u8 {
+    DNA_COMPLEMENT[base as usize]
+}
+
+/// get the reverse complement of a DNA sequence
+pub fn revcomp(seq: &[u8]) -> Vec<u8> {
+    seq.iter()
+        .rev()
+        .map(|&b| DNA_COMPLEMENT[b as usize])
+        .collect()
+}
+
+/// get the reverse complement of a RNA sequence
+pub fn revcomp_rna(seq: &[u8]) -> Vec<u8> {
+    seq.iter()
+        .rev()
+        .map(|&b| RNA_COMPLEMENT[b as usize])
+        .collect()
+}
+
+/// reverse complement a DNA sequence in place
+pub fn revcomp_in_place(seq: &mut [u8]) {
+    seq.reverse();
+    seq.iter_mut()
+        .for_each(|b| *b = DNA_COMPLEMENT[*b as usize]);
+}
+
+/// get the reversed quality string, which matches a reverse-complemented sequence
+pub fn reverse_qual(qual: &[u8]) -> Vec<u8> {
+    qual.iter().rev().copied().collect()
+}
+
+/// check whether a sequence contains soft-masked (lower case) bases
+pub fn is_soft_masked(seq: &[u8]) -> bool {
+    seq.iter().any(u8::is_ascii_lowercase)
+}
+
+/// get the `(start, end)` ranges of soft-masked (lower case) bases
+pub fn soft_masked_regions(seq: &[u8]) -> Vec<(usize, usize)> {
+    let mut regions = Vec::new();
+    let mut start = None;
+    for (i, b) in seq.iter().enumerate() {
+        match (b.is_ascii_lowercase(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                regions.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        regions.push((s, seq.len()));
+    }
+    regions
+}
+
+/// unmask a soft-masked sequence in place, i.e. convert it to upper case
+pub fn unmask(seq: &mut [u8]) {
+    seq.make_ascii_uppercase();
+}
+
+/// hard mask a soft-masked sequence in place, i.e. replace lower case bases with `N`
+pub fn hard_mask(seq: &mut [u8]) {
+    seq.iter_mut()
+        .filter(|b| b.is_ascii_lowercase())
+        .for_each(|b| *b = b'N');
+}
+
+/// replace the bases with a Phred score below `min_qual` with `N`
+pub fn mask_low_qual(seq: &mut [u8], qual: &[u8], offset: u8, min_qual: u8) {
+    seq.iter_mut()
+        .zip(qual)
+        .filter(|(_, &q)| q.saturating_sub(offset) < min_qual)
+        .for_each(|(b, _)| *b = b'N');
+}
+
+/// convert a DNA sequence to RNA in place, i.e. `T` to `U`
+pub fn to_rna(seq: &mut [u8]) {
+    seq.iter_mut().for_each(|b| match *b {
+        b'T' => *b = b'U',
+        b't' => *b = b'u',
+        _ => {}
+    });
+}
+
+/// convert a RNA sequence to DNA in place, i.e. `U` to `T`
+pub fn to_dna(seq: &mut [u8]) {
+    seq.iter_mut().for_each(|b| match *b {
+        b'U' => *b = b'T',
+        b'u' => *b = b't',
+        _ => {}
+    });
+}
+
+/// get the reverse complement of a string, characters other than ASCII are kept as they are
+pub fn revcomp_str(seq: &str) -> String {
+    seq.chars()
+        .rev()
+        .map(|c| {
+            if c.is_ascii() {
+                complement(c as u8) as char
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// get the reverse complement of a borrowed or owned record
+pub fn revcomp_record<R: FastxRecord>(record: &R) -> OwnedFastx {
+    OwnedFastx {
+        head: record.head().to_string(),
+        des: record.des().to_string(),
+        seq: revcomp_str(record.seq()),
+        sep: record.sep().to_string(),
+        qual: record.qual().chars().rev().collect(),
+    }
+}
+
+// apply a function to the bytes of a sequence, which is left unchanged if it is not ASCII,
+// as the fields of an OwnedFastx may contain any characters
+fn edit_ascii<F: FnOnce(&mut [u8])>(s: &mut String, f: F) {
+    if !s.is_ascii() {
+        return;
+    }
+    let mut bytes = mem::take(s).into_bytes();
+    f(&mut bytes);
+    *s = String::from_utf8(bytes)
+        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+}
+
+impl OwnedFastx {
+    /// reverse complement the sequence and reverse the quality in place
+    pub fn revcomp(&mut self) {
+        self.seq = revcomp_str(&self.seq);
+        self.qual = self.qual.chars().rev().collect();
+    }
+
+    /// convert the sequence to upper case
+    pub fn unmask(&mut self) {
+        edit_ascii(&mut self.seq, unmask);
+    }
+
+    /// replace the lower case bases with `N`
+    pub fn hard_mask(&mut self) {
+        edit_ascii(&mut self.seq, hard_mask);
+    }
+
+    /// replace the bases with a Phred score below `min_qual` with `N`
+    pub fn mask_low_qual(&mut self, offset: u8, min_qual: u8) {
+        let qual = self.qual.as_bytes();
+        edit_ascii(&mut self.seq, |seq| {
+            mask_low_qual(seq, qual, offset, min_qual)
+        });
+    }
+
+    /// convert the sequence from DNA to RNA
+    pub fn to_rna(&mut self) {
+        edit_ascii(&mut self.seq, to_rna);
+    }
+
+    /// convert the sequence from RNA to DNA
+    pub fn to_dna(&mut self) {
+        edit_ascii(&mut self.seq, to_dna);
+    }
+}
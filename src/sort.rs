@@ -0,0 +1,360 @@
+//! Sorting of records by name, length or sequence with an external merge sort
+//!
+//! Records are sorted in memory until a memory budget is reached, then the sorted run is
+//! written to a temporary file in a private directory. Runs are merged in batches of a
+//! bounded number of open files until one batch is left, which is merged when records are
+//! iterated, and the temporary files are removed when the iterator is dropped. The sort is
+//! stable, records with equal keys keep their input order.
+//!
+//! ```no_run
+//! use kseq::sort::{SortKey, Sorter};
+//!
+//! let mut records = kseq::parse_path("reads.fq.gz").unwrap();
+//! let mut sorter = Sorter::new(SortKey::Name);
+//! sorter.set_memory(1 << 30);
+//! for record in sorter.sort(&mut records).unwrap() {
+//!     println!("{}", record.unwrap().head);
+//! }
+//! ```
+
+use crate::parse_path;
+use crate::record::{OwnedFastx, ParseError, Result as ParseResult};
+use crate::repair::mate_name;
+use crate::writer::Writer;
+use crate::Paths;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter};
+use std::mem::size_of;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::vec;
+
+// the number of temporary directories created by this process, used to name them
+static NUM_TMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// the minimum memory of records kept in memory
+const MIN_MEMORY: usize = 1 << 20;
+
+/// the key to sort records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// sort by `head()`
+    Name,
+    /// sort by the name of mates, i.e. `head()` without a `/1` or `/2` suffix,
+    /// see [`mate_name`](crate::repair::mate_name)
+    MateName,
+    /// sort by the sequence length
+    Length,
+    /// sort by the sequence
+    Sequence,
+}
+
+/// a sorter of records
+#[derive(Debug, Clone)]
+pub struct Sorter {
+    key: SortKey,
+    /// sort in descending order, default is false
+    pub reverse: bool,
+    memory: usize,
+    batch_size: usize,
+    tmp_dir: PathBuf,
+}
+
+impl Sorter {
+    /// create a new Sorter with a memory budget of 512 MB, a batch size of 64 files and the
+    /// system temporary directory
+    pub fn new(key: SortKey) -> Self {
+        Sorter {
+            key,
+            reverse: false,
+            memory: 512 << 20,
+            batch_size: 64,
+            tmp_dir: std::env::temp_dir(),
+        }
+    }
+
+    /// set the approximate memory (in bytes) of records kept in memory, at least 1 MB
+    pub fn set_memory(&mut self, memory: usize) {
+        self.memory = memory.max(MIN_MEMORY);
+    }
+
+    /// set the maximum number of temporary files merged at once, at least 2
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(2);
+    }
+
+    /// set the directory of temporary files
+    pub fn set_tmp_dir<P: Into<PathBuf>>(&mut self, tmp_dir: P) {
+        self.tmp_dir = tmp_dir.into();
+    }
+
+    fn compare(&self, a: &OwnedFastx, b: &OwnedFastx) -> Ordering {
+        compare(self.key, self.reverse, a, b)
+    }
+
+    /// sort all records of a Reader or Readers, the input must be all fasta or all fastq
+    pub fn sort(&self, paths: &mut Paths) -> ParseResult<SortedRecords> {
+        // temporary files are removed by SortedRecords, even if an error occurs
+        let mut sorted = SortedRecords {
+            inner: Inner::Memory(Vec::new().into_iter()),
+            num_runs: 0,
+            tmp_dir: None,
+        };
+        let mut runs = Vec::new();
+        let mut records = Vec::new();
+        let mut memory = 0;
+        let mut fastq = None;
+        while let Some(record) = paths.iter_record()? {
+            if *fastq.get_or_insert(record.is_fastq()) != record.is_fastq() {
+                return Err(ParseError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "cannot sort a mix of fasta and fastq records",
+                )));
+            }
+            let record = record.to_owned_fastx();
+            memory += record_size(&record);
+            records.push(record);
+            if memory >= self.memory {
+                runs.push(self.write_run(&mut sorted.tmp_dir, &mut records)?);
+                memory = 0;
+            }
+        }
+        if runs.is_empty() {
+            records.sort_by(|a, b| self.compare(a, b));
+            sorted.inner = Inner::Memory(records.into_iter());
+            return Ok(sorted);
+        }
+        if !records.is_empty() {
+            runs.push(self.write_run(&mut sorted.tmp_dir, &mut records)?);
+        }
+        sorted.num_runs = runs.len();
+        // safely unwrap, the directory is created by write_run
+        let tmp_dir = sorted.tmp_dir.as_mut().unwrap();
+        // merge consecutive runs, which keeps the sort stable
+        while runs.len() > self.batch_size {
+            let mut merged = Vec::new();
+            for batch in runs.chunks(self.batch_size) {
+                merged.push(self.merge_runs(tmp_dir, batch)?);
+            }
+            runs = merged;
+        }
+        sorted.inner = Inner::Merge(self.merge(&runs)?);
+        Ok(sorted)
+    }
+
+    // sort records and write them to a temporary file
+    fn write_run(
+        &self,
+        tmp_dir: &mut Option<TmpDir>,
+        records: &mut Vec<OwnedFastx>,
+    ) -> ParseResult<PathBuf> {
+        records.sort_by(|a, b| self.compare(a, b));
+        let tmp_dir = match tmp_dir {
+            Some(tmp_dir) => tmp_dir,
+            None => tmp_dir.insert(TmpDir::new(&self.tmp_dir)?),
+        };
+        let (path, mut writer) = tmp_dir.create()?;
+        for record in records.drain(..) {
+            writer.write_record(&record)?;
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+
+    // open a k-way merge of runs
+    fn merge(&self, runs: &[PathBuf]) -> ParseResult<Merge> {
+        let mut merge = Merge {
+            key: self.key,
+            reverse: self.reverse,
+            readers: Vec::new(),
+            heap: BinaryHeap::new(),
+        };
+        for (i, run) in runs.iter().enumerate() {
+            merge.readers.push(parse_path(run.clone())?);
+            merge.push_next(i)?;
+        }
+        Ok(merge)
+    }
+
+    // merge runs into a new temporary file, and remove them
+    fn merge_runs(&self, tmp_dir: &mut TmpDir, runs: &[PathBuf]) -> ParseResult<PathBuf> {
+        let mut merge = self.merge(runs)?;
+        let (path, mut writer) = tmp_dir.create()?;
+        while let Some(record) = merge.next() {
+            writer.write_record(&record?)?;
+        }
+        writer.flush()?;
+        for run in runs {
+            fs::remove_file(run)?;
+        }
+        Ok(path)
+    }
+}
+
+// a private directory of temporary files, which is removed when dropped
+struct TmpDir {
+    path: PathBuf,
+    num_files: usize,
+}
+
+impl TmpDir {
+    // create a new directory in a parent directory, which is only accessible by the user
+    fn new(parent: &Path) -> io::Result<Self> {
+        loop {
+            let path = parent.join(format!(
+                "kseq-sort-{}-{}",
+                std::process::id(),
+                NUM_TMP_DIRS.fetch_add(1, AtomicOrdering::Relaxed)
+            ));
+            let mut builder = fs::DirBuilder::new();
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            match builder.create(&path) {
+                Ok(()) => return Ok(TmpDir { path, num_files: 0 }),
+                // left by another process with the same id
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // create a new temporary file, which never replaces an existing file
+    fn create(&mut self) -> io::Result<(PathBuf, Writer<BufWriter<File>>)> {
+        let path = self.path.join(format!("run-{}.tmp", self.num_files));
+        self.num_files += 1;
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok((path, Writer::new(BufWriter::with_capacity(65536, file))))
+    }
+}
+
+impl Drop for TmpDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn compare(key: SortKey, reverse: bool, a: &OwnedFastx, b: &OwnedFastx) -> Ordering {
+    let ord = match key {
+        SortKey::Name => a.head.cmp(&b.head),
+        SortKey::MateName => mate_name(&a.head).cmp(mate_name(&b.head)),
+        SortKey::Length => a.seq.len().cmp(&b.seq.len()),
+        SortKey::Sequence => a.seq.cmp(&b.seq),
+    };
+    if reverse {
+        ord.reverse()
+    } else {
+        ord
+    }
+}
+
+// the approximate memory of a record
+fn record_size(record: &OwnedFastx) -> usize {
+    size_of::<OwnedFastx>()
+        + record.head.capacity()
+        + record.des.capacity()
+        + record.seq.capacity()
+        + record.sep.capacity()
+        + record.qual.capacity()
+}
+
+// k-way merge of sorted runs
+struct Merge {
+    key: SortKey,
+    reverse: bool,
+    readers: Vec<Paths<'static>>,
+    heap: BinaryHeap<Entry>,
+}
+
+// an entry of the merge heap, which is ordered by `key` and then by run for stability
+struct Entry {
+    record: OwnedFastx,
+    run: usize,
+    key: SortKey,
+    reverse: bool,
+}
+
+impl Entry {
+    fn compare(&self, other: &Self) -> Ordering {
+        compare(self.key, self.reverse, &self.record, &other.record).then(self.run.cmp(&other.run))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.compare(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // reversed, as BinaryHeap is a max-heap
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.compare(self)
+    }
+}
+
+impl Merge {
+    // read the next record of a run into the heap
+    fn push_next(&mut self, run: usize) -> ParseResult<()> {
+        if let Some(record) = self.readers[run].iter_record()? {
+            self.heap.push(Entry {
+                record: record.to_owned_fastx(),
+                run,
+                key: self.key,
+                reverse: self.reverse,
+            });
+        }
+        Ok(())
+    }
+
+    fn next(&mut self) -> Option<ParseResult<OwnedFastx>> {
+        let entry = self.heap.pop()?;
+        match self.push_next(entry.run) {
+            Ok(()) => Some(Ok(entry.record)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+enum Inner {
+    Memory(vec::IntoIter<OwnedFastx>),
+    Merge(Merge),
+}
+
+/// an iterator over sorted records, which removes temporary files when dropped
+pub struct SortedRecords {
+    inner: Inner,
+    num_runs: usize,
+    // dropped after the readers of runs
+    tmp_dir: Option<TmpDir>,
+}
+
+impl SortedRecords {
+    /// get the number of sorted runs written to temporary files, 0 if all records fit in memory
+    pub fn num_runs(&self) -> usize {
+        self.num_runs
+    }
+}
+
+impl Iterator for SortedRecords {
+    type Item = ParseResult<OwnedFastx>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            Inner::Memory(records) => records.next().map(Ok),
+            Inner::Merge(merge) => merge.next(),
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Splitting of records into multiple files
+//!
+//! Records are split into a number of parts, parts of a number of records or bases, or by a
+//! key of each record such as a sample tag in `des()`. Output paths are built from a template
+//! with the placeholders:
+//! - `{part}`: the 1-based part number, padded to 3 digits
+//! - `{key}`: the key of records
+//! - `{mate}`: `1` or `2` for paired files
+//! - `{ext}`: `fq` for fastq records and `fa` for fasta records
+//!
+//! Outputs are gzip compressed if the template ends with `.gz`. At most
+//! [`DEFAULT_MAX_OPEN`] parts are kept open by default, the least recently used part is
+//! closed for a new one and reopened in append mode when it gets more records.
+//!
+//! ```no_run
+//! use kseq::split::{SplitBy, Splitter};
+//!
+//! let mut records = kseq::parse_path("reads.fq.gz").unwrap();
+//! let splitter = Splitter::new(SplitBy::Records(1_000_000), "reads.part_{part}.fq.gz");
+//! let paths = splitter.run(&mut records).unwrap();
+//! ```
+
+use crate::record::{Fastx, Result as ParseResult};
+use crate::writer::{append_path, create_path, Output, Writer};
+use crate::{each_pair, Paths};
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+
+/// a function to get the key of a record
+pub type KeyFn = Box<dyn Fn(&Fastx) -> Option<String>>;
+
+/// the key of records without a key or with an empty key
+pub const NO_KEY: &str = "unmatched";
+
+/// the default maximum number of parts kept open, a part of paired files has two open files
+pub const DEFAULT_MAX_OPEN: usize = 256;
+
+/// the way to split records
+pub enum SplitBy {
+    /// split into a number of parts, records are distributed in turn
+    Parts(usize),
+    /// split into parts of a number of records
+    Records(usize),
+    /// split into parts of at least a number of bases, except the last part
+    Bases(usize),
+    /// split by a key of each record, records without a key or with an empty key go to
+    /// [`NO_KEY`]
+    Key(KeyFn),
+}
+
+impl SplitBy {
+    /// split by the value of a `tag` in `des()`, e.g. `sample=` for `@r1 sample=A barcode=ACGT`
+    pub fn des_tag(tag: &str) -> Self {
+        let tag = tag.to_string();
+        SplitBy::Key(Box::new(move |record: &Fastx| {
+            record
+                .des()
+                .split_whitespace()
+                .find_map(|field| field.strip_prefix(tag.as_str()))
+                .map(str::to_string)
+        }))
+    }
+
+    /// split by the first capture group (or the whole match if there is no group)
+    /// of a regex in `des()`, requires the `regex` feature
+    #[cfg(feature = "regex")]
+    pub fn des_regex(re: Regex) -> Self {
+        SplitBy::Key(Box::new(move |record: &Fastx| {
+            re.captures(record.des())
+                .and_then(|caps| caps.get(1).or_else(|| caps.get(0)))
+                .map(|m| m.as_str().to_string())
+        }))
+    }
+}
+
+// the writers of a part
+struct Part {
+    writer: Writer<Output>,
+    // the writer of mates for paired files
+    mate: Option<Writer<Output>>,
+    // the number of records when the part was last written
+    last_used: usize,
+}
+
+/// a splitter of records into multiple files
+pub struct Splitter {
+    by: SplitBy,
+    template: String,
+    outputs: HashMap<String, Part>,
+    // the shards whose parts were closed to open others, which are reopened in append mode
+    closed: HashSet<String>,
+    max_open: usize,
+    // the shard of the last record, whose writers are closed once the shard changes
+    // for sequential parts
+    last: Option<String>,
+    paths: Vec<PathBuf>,
+    num_records: usize,
+    part: usize,
+    part_bases: usize,
+}
+
+impl Splitter {
+    /// create a new Splitter with a template of output paths
+    pub fn new(by: SplitBy, template: &str) -> Self {
+        Splitter {
+            by,
+            template: template.to_string(),
+            outputs: HashMap::new(),
+            closed: HashSet::new(),
+            max_open: DEFAULT_MAX_OPEN,
+            last: None,
+            paths: Vec::new(),
+            num_records: 0,
+            part: 0,
+            part_bases: 0,
+        }
+    }
+
+    /// set the maximum number of parts kept open, at least 1
+    pub fn set_max_open(&mut self, max_open: usize) {
+        self.max_open = max_open.max(1);
+    }
+
+    /// split all records of a Reader or Readers, return the created paths
+    pub fn run(mut self, paths: &mut Paths) -> ParseResult<Vec<PathBuf>> {
+        while let Some(record) = paths.iter_record()? {
+            self.write(&record)?;
+        }
+        Ok(self.finish()?)
+    }
+
+    /// split all pairs of records of two paired Readers, the template must contain `{mate}`,
+    /// return the created paths.
+    pub fn run_paired(
+        mut self,
+        paths1: &mut Paths,
+        paths2: &mut Paths,
+    ) -> ParseResult<Vec<PathBuf>> {
+        each_pair(paths1, paths2, |r1, r2| Ok(self.write_pair(r1, r2)?))?;
+        Ok(self.finish()?)
+    }
+
+    /// write a record to its part
+    pub fn write(&mut self, record: &Fastx) -> io::Result<()> {
+        let output = self.output(record, None)?;
+        output.writer.write_record(record)
+    }
+
+    /// write a pair of records to their part, the part is decided by the first record
+    /// (and the bases of both records)
+    pub fn write_pair(&mut self, r1: &Fastx, r2: &Fastx) -> io::Result<()> {
+        let output = self.output(r1, Some(r2))?;
+        output.writer.write_record(r1)?;
+        // safely unwrap, the writer of mates is opened for pairs
+        output.mate.as_mut().unwrap().write_record(r2)
+    }
+
+    /// flush and close all outputs, return the created paths
+    pub fn finish(mut self) -> io::Result<Vec<PathBuf>> {
+        for (_, output) in self.outputs.drain() {
+            output.finish()?;
+        }
+        Ok(self.paths)
+    }
+
+    // get the shard of a record, i.e. a part number or a key
+    fn shard(&mut self, record: &Fastx, mate: Option<&Fastx>) -> String {
+        let i = self.num_records;
+        self.num_records += 1;
+        match &self.by {
+            SplitBy::Parts(n) => format!("{:03}", i % (*n).max(1) + 1),
+            SplitBy::Records(n) => format!("{:03}", i / (*n).max(1) + 1),
+            SplitBy::Bases(n) => {
+                if self.part == 0 || self.part_bases >= *n {
+                    self.part += 1;
+                    self.part_bases = 0;
+                }
+                self.part_bases += record.len() + mate.map_or(0, |r| r.len());
+                format!("{:03}", self.part)
+            }
+            SplitBy::Key(f) => f(record)
+                .filter(|key| !key.is_empty())
+                .map(|key| sanitize(&key))
+                .unwrap_or_else(|| NO_KEY.to_string()),
+        }
+    }
+
+    fn output(&mut self, record: &Fastx, mate: Option<&Fastx>) -> io::Result<&mut Part> {
+        let shard = self.shard(record, mate);
+        let sequential = matches!(self.by, SplitBy::Records(_) | SplitBy::Bases(_));
+        if sequential && self.last.as_ref() != Some(&shard) {
+            if let Some(output) = self.last.take().and_then(|last| self.outputs.remove(&last)) {
+                output.finish()?;
+            }
+            self.last = Some(shard.clone());
+        }
+        let ext = if record.is_fastq() { "fq" } else { "fa" };
+        if !self.outputs.contains_key(&shard) {
+            if self.outputs.len() >= self.max_open {
+                self.close_least_used()?;
+            }
+            let append = self.closed.remove(&shard);
+            let writer = self.create(&shard, "1", ext, mate.is_some(), append)?;
+            let mate = match mate {
+                Some(_) => Some(self.create(&shard, "2", ext, true, append)?),
+                None => None,
+            };
+            let part = Part {
+                writer,
+                mate,
+                last_used: 0,
+            };
+            self.outputs.insert(shard.clone(), part);
+        } else if mate.is_some() && self.outputs[&shard].mate.is_none() {
+            // the part was opened by `write`, open the writer of mates for the first pair
+            let mate = self.create(&shard, "2", ext, true, true)?;
+            // safely unwrap, the shard is a key of outputs
+            self.outputs.get_mut(&shard).unwrap().mate = Some(mate);
+        }
+        // safely unwrap, the output is inserted above
+        let output = self.outputs.get_mut(&shard).unwrap();
+        output.last_used = self.num_records;
+        Ok(output)
+    }
+
+    // close the least recently used part to open another one
+    fn close_least_used(&mut self) -> io::Result<()> {
+        let shard = self
+            .outputs
+            .iter()
+            .min_by_key(|(_, output)| output.last_used)
+            .map(|(shard, _)| shard.clone());
+        if let Some(shard) = shard {
+            // safely unwrap, the shard is a key of outputs
+            self.outputs.remove(&shard).unwrap().finish()?;
+            self.closed.insert(shard);
+        }
+        Ok(())
+    }
+
+    fn create(
+        &mut self,
+        shard: &str,
+        mate: &str,
+        ext: &str,
+        paired: bool,
+        append: bool,
+    ) -> io::Result<Writer<Output>> {
+        let placeholder = match self.by {
+            SplitBy::Key(_) => "{key}",
+            _ => "{part}",
+        };
+        if !self.template.contains(placeholder) || (paired && !self.template.contains("{mate}")) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the template {} must contain {}{}",
+                    self.template,
+                    placeholder,
+                    if paired { " and {mate}" } else { "" }
+                ),
+            ));
+        }
+        let path = self
+            .template
+            .replace(placeholder, shard)
+            .replace("{mate}", mate)
+            .replace("{ext}", ext);
+        // a file which was not created yet, e.g. the mates of a part opened by `write`,
+        // is created even in append mode
+        if append && self.paths.iter().any(|p| p.as_os_str() == path.as_str()) {
+            return append_path(&path);
+        }
+        let writer = create_path(&path)?;
+        self.paths.push(PathBuf::from(path));
+        Ok(writer)
+    }
+}
+
+impl Part {
+    fn finish(self) -> io::Result<()> {
+        self.writer.finish()?;
+        if let Some(writer) = self.mate {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+// replace the characters of a key which are not safe in a file name, including the dots
+// of a key of only dots, e.g. `..`
+fn sanitize(key: &str) -> String {
+    if key.chars().all(|c| c == '.') {
+        return "_".repeat(key.len());
+    }
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
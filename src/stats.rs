@@ -0,0 +1,251 @@
+//! Sequence file statistics, similar to `seqkit stats`
+//!
+//! ```no_run
+//! let mut records = kseq::parse_path("reads.fq.gz").unwrap();
+//! let stats = kseq::stats::Stats::from_paths(&mut records).unwrap();
+//! println!("{}\n{}", kseq::stats::Summary::tsv_header(), stats.summary());
+//! ```
+
+use crate::record::{FastxRecord, Result as ParseResult};
+use crate::Paths;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// an accumulator of statistics, which can be updated record by record
+/// and merged with the statistics of other parts of a dataset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// the ASCII offset of quality scores, default is 33
+    pub offset: u8,
+    num_seqs: u64,
+    num_bases: u64,
+    num_gc: u64,
+    num_n: u64,
+    num_qual: u64,
+    num_q20: u64,
+    num_q30: u64,
+    // sequence length -> count
+    lengths: BTreeMap<usize, u64>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stats {
+    /// create an empty Stats
+    pub fn new() -> Self {
+        Stats {
+            offset: 33,
+            num_seqs: 0,
+            num_bases: 0,
+            num_gc: 0,
+            num_n: 0,
+            num_qual: 0,
+            num_q20: 0,
+            num_q30: 0,
+            lengths: BTreeMap::new(),
+        }
+    }
+
+    /// collect the statistics of all records of a Reader or Readers
+    pub fn from_paths(paths: &mut Paths) -> ParseResult<Self> {
+        let mut stats = Stats::new();
+        while let Some(record) = paths.iter_record()? {
+            stats.update(&record);
+        }
+        Ok(stats)
+    }
+
+    /// add a record to the statistics
+    pub fn update<R: FastxRecord>(&mut self, record: &R) {
+        let seq = record.seq().as_bytes();
+        self.num_seqs += 1;
+        self.num_bases += seq.len() as u64;
+        *self.lengths.entry(seq.len()).or_insert(0) += 1;
+        for &b in seq {
+            match b {
+                b'G' | b'C' | b'g' | b'c' => self.num_gc += 1,
+                b'N' | b'n' => self.num_n += 1,
+                _ => {}
+            }
+        }
+        let qual = record.qual().as_bytes();
+        self.num_qual += qual.len() as u64;
+        for &q in qual {
+            let q = q.saturating_sub(self.offset);
+            if q >= 20 {
+                self.num_q20 += 1;
+                if q >= 30 {
+                    self.num_q30 += 1;
+                }
+            }
+        }
+    }
+
+    /// merge the statistics of another part of a dataset into this Stats
+    pub fn merge(&mut self, other: &Stats) {
+        self.num_seqs += other.num_seqs;
+        self.num_bases += other.num_bases;
+        self.num_gc += other.num_gc;
+        self.num_n += other.num_n;
+        self.num_qual += other.num_qual;
+        self.num_q20 += other.num_q20;
+        self.num_q30 += other.num_q30;
+        for (&len, &count) in &other.lengths {
+            *self.lengths.entry(len).or_insert(0) += count;
+        }
+    }
+
+    /// get the number of records
+    pub fn num_seqs(&self) -> u64 {
+        self.num_seqs
+    }
+
+    /// get the number of bases
+    pub fn num_bases(&self) -> u64 {
+        self.num_bases
+    }
+
+    /// get the minimum sequence length, 0 if there is no record
+    pub fn min_len(&self) -> usize {
+        self.lengths.keys().next().copied().unwrap_or(0)
+    }
+
+    /// get the maximum sequence length, 0 if there is no record
+    pub fn max_len(&self) -> usize {
+        self.lengths.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// get the mean sequence length, 0 if there is no record
+    pub fn mean_len(&self) -> f64 {
+        ratio(self.num_bases, self.num_seqs)
+    }
+
+    /// get the Nx length, i.e. the length of the shortest sequence among the longest
+    /// sequences that cover `x`% of all bases, e.g. `nx(50.0)` for N50.
+    pub fn nx(&self, x: f64) -> usize {
+        let target = self.num_bases as f64 * x / 100.0;
+        let mut sum = 0;
+        for (&len, &count) in self.lengths.iter().rev() {
+            sum += len as u64 * count;
+            if sum as f64 >= target {
+                return len;
+            }
+        }
+        0
+    }
+
+    /// get the N50 length
+    pub fn n50(&self) -> usize {
+        self.nx(50.0)
+    }
+
+    /// get the N90 length
+    pub fn n90(&self) -> usize {
+        self.nx(90.0)
+    }
+
+    /// get the number of `N` bases
+    pub fn num_n(&self) -> u64 {
+        self.num_n
+    }
+
+    /// get the GC content (%) of all bases
+    pub fn gc_content(&self) -> f64 {
+        ratio(self.num_gc, self.num_bases) * 100.0
+    }
+
+    /// get the fraction (%) of bases with a Phred score >= 20, 0 for fasta files
+    pub fn q20(&self) -> f64 {
+        ratio(self.num_q20, self.num_qual) * 100.0
+    }
+
+    /// get the fraction (%) of bases with a Phred score >= 30, 0 for fasta files
+    pub fn q30(&self) -> f64 {
+        ratio(self.num_q30, self.num_qual) * 100.0
+    }
+
+    /// get a summary of the statistics
+    pub fn summary(&self) -> Summary {
+        Summary {
+            num_seqs: self.num_seqs,
+            num_bases: self.num_bases,
+            min_len: self.min_len(),
+            mean_len: self.mean_len(),
+            max_len: self.max_len(),
+            n50: self.n50(),
+            n90: self.n90(),
+            num_n: self.num_n,
+            gc_content: self.gc_content(),
+            q20: self.q20(),
+            q30: self.q30(),
+        }
+    }
+}
+
+fn ratio(a: u64, b: u64) -> f64 {
+    if b == 0 {
+        0.0
+    } else {
+        a as f64 / b as f64
+    }
+}
+
+/// a summary of statistics, which is displayed as a tab-separated line
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    /// number of records
+    pub num_seqs: u64,
+    /// number of bases
+    pub num_bases: u64,
+    /// minimum sequence length
+    pub min_len: usize,
+    /// mean sequence length
+    pub mean_len: f64,
+    /// maximum sequence length
+    pub max_len: usize,
+    /// N50 length
+    pub n50: usize,
+    /// N90 length
+    pub n90: usize,
+    /// number of `N` bases
+    pub num_n: u64,
+    /// GC content (%)
+    pub gc_content: f64,
+    /// fraction (%) of bases with a Phred score >= 20
+    pub q20: f64,
+    /// fraction (%) of bases with a Phred score >= 30
+    pub q30: f64,
+}
+
+impl Summary {
+    /// get the tab-separated header of the displayed summary
+    pub fn tsv_header() -> &'static str {
+        "num_seqs\tnum_bases\tmin_len\tmean_len\tmax_len\tN50\tN90\tnum_N\tGC(%)\tQ20(%)\tQ30(%)"
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{:.1}\t{}\t{}\t{}\t{}\t{:.2}\t{:.2}\t{:.2}",
+            self.num_seqs,
+            self.num_bases,
+            self.min_len,
+            self.mean_len,
+            self.max_len,
+            self.n50,
+            self.n90,
+            self.num_n,
+            self.gc_content,
+            self.q20,
+            self.q30
+        )
+    }
+}
@@ -0,0 +1,255 @@
+//! Record transforms for quality/length trimming and filtering
+//!
+//! A transform takes an [`OwnedFastx`] and returns the transformed record, or `None` if the
+//! record is discarded. Transforms can be chained with a [`Pipeline`] and applied to the
+//! records returned by `iter_record`:
+//!
+//! ```no_run
+//! use kseq::transform::{MinLen, Pipeline, PolyTrim, QualityTrim};
+//!
+//! let mut pipeline = Pipeline::new()
+//!     .then(QualityTrim::new(4, 20, 33))
+//!     .then(PolyTrim::new(b'G', 10))
+//!     .then(MinLen(50));
+//! let mut records = kseq::parse_path("reads.fq.gz").unwrap();
+//! while let Some(record) = records.iter_record().unwrap() {
+//!     if let Some(record) = pipeline.apply(&record) {
+//!         println!("{}", record.seq);
+//!     }
+//! }
+//! ```
+
+use crate::record::{Fastx, FastxRecord, OwnedFastx};
+
+/// an operation on an owned record
+pub trait Transform {
+    /// transform a record, return `None` to discard it
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx>;
+}
+
+impl<F: FnMut(OwnedFastx) -> Option<OwnedFastx>> Transform for F {
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        self(record)
+    }
+}
+
+/// a sequence of transforms applied in order, stops at the first transform discarding the record
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    /// create an empty Pipeline
+    pub fn new() -> Self {
+        Pipeline { steps: Vec::new() }
+    }
+
+    /// append a transform to this Pipeline
+    pub fn then<T: Transform + 'static>(mut self, step: T) -> Self {
+        self.steps.push(Box::new(step));
+        self
+    }
+
+    /// copy a record and apply all transforms to it
+    pub fn apply(&mut self, record: &Fastx) -> Option<OwnedFastx> {
+        self.apply_owned(record.to_owned_fastx())
+    }
+
+    /// apply all transforms to an owned record
+    pub fn apply_owned(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        self.steps
+            .iter_mut()
+            .try_fold(record, |record, step| step.apply(record))
+    }
+}
+
+impl Transform for Pipeline {
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        self.apply_owned(record)
+    }
+}
+
+/// sliding-window quality trimming, similar to `--cut_front` and `--cut_tail` of fastp.
+/// The window moves from an end to the other end, the bases in the window are dropped
+/// while its mean quality is below `min_qual`. Fasta records are left unchanged.
+#[derive(Debug, Clone)]
+pub struct QualityTrim {
+    /// window size
+    pub window: usize,
+    /// the minimum mean Phred score of a window to stop trimming
+    pub min_qual: u8,
+    /// the ASCII offset of quality scores
+    pub offset: u8,
+    /// trim from the 5' end
+    pub front: bool,
+    /// trim from the 3' end
+    pub tail: bool,
+}
+
+impl QualityTrim {
+    /// create a QualityTrim trimming both ends
+    pub fn new(window: usize, min_qual: u8, offset: u8) -> Self {
+        QualityTrim {
+            window,
+            min_qual,
+            offset,
+            front: true,
+            tail: true,
+        }
+    }
+
+    // get the trimmed range of a quality string
+    fn range(&self, qual: &[u8]) -> (usize, usize) {
+        let len = qual.len();
+        if len == 0 {
+            return (0, 0);
+        }
+        let window = self.window.clamp(1, len);
+        let min_sum = self.min_qual as usize * window;
+        let scores: Vec<usize> = qual
+            .iter()
+            .map(|&q| q.saturating_sub(self.offset) as usize)
+            .collect();
+
+        let mut start = 0;
+        if self.front {
+            let mut sum: usize = scores.iter().take(window).sum();
+            start = len;
+            for i in 0..=len.saturating_sub(window) {
+                if i > 0 {
+                    sum = sum + scores[i + window - 1] - scores[i - 1];
+                }
+                if sum >= min_sum {
+                    start = i;
+                    break;
+                }
+            }
+        }
+
+        let mut end = len;
+        if self.tail {
+            let mut sum: usize = scores.iter().rev().take(window).sum();
+            end = start;
+            for i in (start..=len.saturating_sub(window)).rev() {
+                if i + window < len {
+                    sum = sum + scores[i] - scores[i + window];
+                }
+                if sum >= min_sum {
+                    end = i + window;
+                    break;
+                }
+            }
+        }
+        (start, end.max(start))
+    }
+}
+
+impl Transform for QualityTrim {
+    fn apply(&mut self, mut record: OwnedFastx) -> Option<OwnedFastx> {
+        if record.is_fastq() {
+            let (start, end) = self.range(record.qual.as_bytes());
+            record.trim(start, end);
+        }
+        Some(record)
+    }
+}
+
+/// remove a fixed number of bases from the 5' end (`head`) and the 3' end (`tail`)
+#[derive(Debug, Clone)]
+pub struct Crop {
+    /// the number of bases to remove from the 5' end
+    pub head: usize,
+    /// the number of bases to remove from the 3' end
+    pub tail: usize,
+}
+
+impl Transform for Crop {
+    fn apply(&mut self, mut record: OwnedFastx) -> Option<OwnedFastx> {
+        let end = record.len().saturating_sub(self.tail);
+        record.trim(self.head, end);
+        Some(record)
+    }
+}
+
+/// trim a homopolymer tail at the 3' end, such as poly-G of two-color sequencers or poly-A,
+/// allowing one mismatch per 8 bases.
+#[derive(Debug, Clone)]
+pub struct PolyTrim {
+    /// the base of the tail, case-insensitive
+    pub base: u8,
+    /// the minimum length of a tail to trim
+    pub min_len: usize,
+}
+
+impl PolyTrim {
+    /// create a PolyTrim
+    pub fn new(base: u8, min_len: usize) -> Self {
+        PolyTrim { base, min_len }
+    }
+
+    // get the length of the tail
+    fn tail_len(&self, seq: &[u8]) -> usize {
+        let base = self.base.to_ascii_uppercase();
+        let mut mismatch = 0;
+        let mut tail = 0;
+        for (i, &b) in seq.iter().rev().enumerate() {
+            let len = i + 1;
+            if b.to_ascii_uppercase() == base {
+                if mismatch * 8 <= len {
+                    tail = len;
+                }
+            } else {
+                mismatch += 1;
+                if mismatch * 8 > len + 8 {
+                    break;
+                }
+            }
+        }
+        tail
+    }
+}
+
+impl Transform for PolyTrim {
+    fn apply(&mut self, mut record: OwnedFastx) -> Option<OwnedFastx> {
+        let tail = self.tail_len(record.seq.as_bytes());
+        if tail > 0 && tail >= self.min_len {
+            let end = record.len() - tail;
+            record.trim(0, end);
+        }
+        Some(record)
+    }
+}
+
+/// discard records shorter than the given length
+#[derive(Debug, Clone)]
+pub struct MinLen(pub usize);
+
+impl Transform for MinLen {
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        if record.len() >= self.0 {
+            Some(record)
+        } else {
+            None
+        }
+    }
+}
+
+/// discard records whose fraction of `N` bases is larger than the given fraction
+#[derive(Debug, Clone)]
+pub struct MaxNFraction(pub f64);
+
+impl Transform for MaxNFraction {
+    fn apply(&mut self, record: OwnedFastx) -> Option<OwnedFastx> {
+        let n = record
+            .seq
+            .bytes()
+            .filter(|&b| b == b'N' || b == b'n')
+            .count();
+        if n as f64 <= self.0 * record.len() as f64 {
+            Some(record)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! UMI extraction into read headers, similar to `umi_tools extract`
+//!
+//! A pattern such as `NNNNNNNNXXXX` describes the start of reads: the `N` bases are the UMI,
+//! which is removed from the sequence and quality, and the `X` bases are kept in the read.
+//! The UMI is appended to the read name (`@r1_ACGTACGT`) or added to the comment as a SAM
+//! `RX:Z:` tag (`@r1 RX:Z:ACGTACGT`).
+//!
+//! ```
+//! use kseq::record::OwnedFastx;
+//! use kseq::transform::Transform;
+//! use kseq::umi::UmiExtractor;
+//!
+//! let mut extractor = UmiExtractor::new("NNNNX").unwrap();
+//! let record = OwnedFastx { head: "r1".into(), seq: "ACGTTCCC".into(), ..Default::default() };
+//! let record = extractor.apply(record).unwrap();
+//! assert_eq!((record.head.as_str(), record.seq.as_str()), ("r1_ACGT", "TCCC"));
+//! ```
+
+use crate::record::OwnedFastx;
+use crate::transform::Transform;
+use std::io;
+
+/// where to put UMIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UmiPlacement {
+    /// append to the read name with a separator, e.g. `r1_ACGTACGT`
+    Name(char),
+    /// add a `RX:Z:` tag to the comment, e.g. `r1 RX:Z:ACGTACGT`
+    Tag,
+}
+
+// a parsed pattern, true for the bases of UMIs
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern(Vec<bool>);
+
+impl Pattern {
+    fn new(pattern: &str) -> io::Result<Self> {
+        pattern
+            .bytes()
+            .map(|b| match b.to_ascii_uppercase() {
+                b'N' => Ok(true),
+                b'X' => Ok(false),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid UMI pattern {}, only N and X are allowed", pattern),
+                )),
+            })
+            .collect::<io::Result<Vec<_>>>()
+            .map(Pattern)
+    }
+
+    // move the UMI bases of a record into `umi`, return false if the record is too short
+    // or not ASCII, as the bases are cut by bytes
+    fn extract(&self, record: &mut OwnedFastx, umi: &mut String, three_prime: bool) -> bool {
+        let len = self.0.len();
+        if record.seq.len() < len || !record.seq.is_ascii() || !record.qual.is_ascii() {
+            return false;
+        }
+        let start = if three_prime {
+            record.seq.len() - len
+        } else {
+            0
+        };
+        let is_umi = |i: usize| i >= start && i < start + len && self.0[i - start];
+        umi.extend(
+            record.seq.as_bytes()[start..start + len]
+                .iter()
+                .zip(&self.0)
+                .filter(|(_, &is_umi)| is_umi)
+                .map(|(&b, _)| b as char),
+        );
+        let keep = |s: &str| -> String {
+            s.bytes()
+                .enumerate()
+                .filter(|&(i, _)| !is_umi(i))
+                .map(|(_, b)| b as char)
+                .collect()
+        };
+        record.seq = keep(&record.seq);
+        if !record.qual.is_empty() {
+            record.qual = keep(&record.qual);
+        }
+        true
+    }
+}
+
+/// a transform moving UMIs from sequences to read headers,
+/// reads shorter than the pattern or with non-ASCII sequences or qualities are discarded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UmiExtractor {
+    pattern: Pattern,
+    mate_pattern: Option<Pattern>,
+    /// where to put UMIs, default is `UmiPlacement::Name('_')`
+    pub placement: UmiPlacement,
+    /// the pattern describes the 3' end of reads instead of the 5' end, default is false
+    pub three_prime: bool,
+}
+
+impl UmiExtractor {
+    /// create a new UmiExtractor with a pattern of `N` (UMI) and `X` (kept) bases,
+    /// return an error if the pattern has other characters.
+    pub fn new(pattern: &str) -> io::Result<Self> {
+        Ok(UmiExtractor {
+            pattern: Pattern::new(pattern)?,
+            mate_pattern: None,
+            placement: UmiPlacement::Name('_'),
+            three_prime: false,
+        })
+    }
+
+    /// set the pattern of the second reads of pairs, whose UMIs are appended to the UMIs
+    /// of the first reads. Without it, the UMIs of first reads are only copied to mates.
+    pub fn set_mate_pattern(&mut self, pattern: &str) -> io::Result<()> {
+        self.mate_pattern = Some(Pattern::new(pattern)?);
+        Ok(())
+    }
+
+    /// extract the UMI of a pair of reads, the UMI is added to the headers of both reads,
+    /// return `None` if any read is shorter than its pattern.
+    pub fn apply_pair(
+        &self,
+        mut r1: OwnedFastx,
+        mut r2: OwnedFastx,
+    ) -> Option<(OwnedFastx, OwnedFastx)> {
+        let mut umi = String::new();
+        if !self.pattern.extract(&mut r1, &mut umi, self.three_prime) {
+            return None;
+        }
+        if let Some(pattern) = &self.mate_pattern {
+            if !pattern.extract(&mut r2, &mut umi, self.three_prime) {
+                return None;
+            }
+        }
+        self.annotate(&mut r1, &umi);
+        self.annotate(&mut r2, &umi);
+        Some((r1, r2))
+    }
+
+    // add a UMI to the header of a record
+    fn annotate(&self, record: &mut OwnedFastx, umi: &str) {
+        match self.placement {
+            UmiPlacement::Name(sep) => {
+                record.head.push(sep);
+                record.head.push_str(umi);
+            }
+            UmiPlacement::Tag => {
+                record.des.push_str(" RX:Z:");
+                record.des.push_str(umi);
+            }
+        }
+    }
+}
+
+impl Transform for UmiExtractor {
+    fn apply(&mut self, mut record: OwnedFastx) -> Option<OwnedFastx> {
+        let mut umi = String::new();
+        if !self
+            .pattern
+            .extract(&mut record, &mut umi, self.three_prime)
+        {
+            return None;
+        }
+        self.annotate(&mut record, &umi);
+        Some(record)
+    }
+}
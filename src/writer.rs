@@ -0,0 +1,161 @@
+//! Writing fasta/fastq records
+
+use crate::record::FastxRecord;
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    fs::{File, OpenOptions},
+    io::{stdout, BufWriter, Result, Stdout, Write},
+    path::Path,
+};
+
+/// a writer of fasta/fastq records
+pub struct Writer<W: Write> {
+    writer: W,
+    line_width: usize,
+}
+
+impl<W: Write> Writer<W> {
+    /// create a new Writer, which writes sequences and qualities in a single line
+    pub fn new(writer: W) -> Self {
+        Writer {
+            writer,
+            line_width: 0,
+        }
+    }
+
+    /// set the maximum line width of sequences and qualities, 0 for a single line
+    pub fn set_line_width(&mut self, line_width: usize) {
+        self.line_width = line_width;
+    }
+
+    /// write a record in its own format, i.e. fasta for a fasta record and fastq for a fastq record
+    pub fn write_record<R: FastxRecord>(&mut self, record: &R) -> Result<()> {
+        if record.is_fastq() {
+            self.write_fastq(record)
+        } else {
+            self.write_fasta(record)
+        }
+    }
+
+    /// write a record as fasta, the quality scores (if any) are dropped
+    pub fn write_fasta<R: FastxRecord>(&mut self, record: &R) -> Result<()> {
+        self.write_head(b'>', record)?;
+        self.write_lines(record.seq().as_bytes())
+    }
+
+    /// write a fastq record, the separator line is written as is
+    pub fn write_fastq<R: FastxRecord>(&mut self, record: &R) -> Result<()> {
+        self.write_head(b'@', record)?;
+        self.write_lines(record.seq().as_bytes())?;
+        match record.sep() {
+            "" => self.writer.write_all(b"+")?,
+            sep => self.writer.write_all(sep.as_bytes())?,
+        }
+        self.writer.write_all(b"\n")?;
+        self.write_lines(record.qual().as_bytes())
+    }
+
+    // write a sequence or quality wrapped at the line width
+    fn write_lines(&mut self, data: &[u8]) -> Result<()> {
+        if self.line_width == 0 || data.is_empty() {
+            self.writer.write_all(data)?;
+            return self.writer.write_all(b"\n");
+        }
+        for line in data.chunks(self.line_width) {
+            self.writer.write_all(line)?;
+            self.writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn write_head<R: FastxRecord>(&mut self, marker: u8, record: &R) -> Result<()> {
+        self.writer.write_all(&[marker])?;
+        self.writer.write_all(record.head().as_bytes())?;
+        self.writer.write_all(record.des().as_bytes())?;
+        self.writer.write_all(b"\n")
+    }
+
+    /// flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+
+    /// get the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// the output of a Writer created by [`create_path`]
+pub enum Output {
+    /// the standard output
+    Stdout(BufWriter<Stdout>),
+    /// a plain file
+    File(BufWriter<File>),
+    /// a gzip compressed file
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        match self {
+            Output::Stdout(w) => w.write(buf),
+            Output::File(w) => w.write(buf),
+            Output::Gzip(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Output::Stdout(w) => w.flush(),
+            Output::File(w) => w.flush(),
+            Output::Gzip(w) => w.flush(),
+        }
+    }
+}
+
+impl Writer<Output> {
+    /// flush the output and write the trailer of a gzip compressed file, which must be called
+    /// to get the errors of writing the trailer, as they are ignored when a Writer is dropped
+    pub fn finish(self) -> Result<()> {
+        match self.writer {
+            Output::Stdout(mut w) => w.flush(),
+            Output::File(mut w) => w.flush(),
+            Output::Gzip(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+/// create a Writer to a path, `-` for `io::stdout`,
+/// the output is gzip compressed if the path ends with `.gz`.
+pub fn create_path<P: AsRef<Path>>(path: P) -> Result<Writer<Output>> {
+    open_path(path.as_ref(), false)
+}
+
+/// create a Writer appending to a path like [`create_path`], the file is created if it
+/// doesn't exist. A gzip compressed file gets a new gzip member, which is read as a
+/// single stream by `parse_path`.
+pub fn append_path<P: AsRef<Path>>(path: P) -> Result<Writer<Output>> {
+    open_path(path.as_ref(), true)
+}
+
+fn open_path(path: &Path, append: bool) -> Result<Writer<Output>> {
+    let open = |path: &Path| {
+        if append {
+            OpenOptions::new().append(true).create(true).open(path)
+        } else {
+            File::create(path)
+        }
+    };
+    let writer = if path == Path::new("-") {
+        Output::Stdout(BufWriter::with_capacity(65536, stdout()))
+    } else if path.extension().is_some_and(|ext| ext == "gz") {
+        Output::Gzip(GzEncoder::new(
+            BufWriter::with_capacity(65536, open(path)?),
+            Compression::default(),
+        ))
+    } else {
+        Output::File(BufWriter::with_capacity(65536, open(path)?))
+    };
+    Ok(Writer::new(writer))
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::fastq;
+use kseq::adapter::{Adapter, AdapterTrimmer, NEXTERA, TRUSEQ};
+use kseq::seq::revcomp_str;
+
+static INSERT: &str = "GATTACACCGTAGCTAGGCTTACGATCGAT";
+
+#[test]
+fn test_trim_full_adapter() {
+    let mut trimmer = AdapterTrimmer::default();
+    let record = trimmer.trim(fastq(&format!("{}{}GGGG", INSERT, TRUSEQ)));
+    assert_eq!(record.seq, INSERT);
+    assert_eq!(record.qual.len(), INSERT.len());
+    let record = trimmer.trim(fastq(&format!("{}{}", INSERT, NEXTERA)));
+    assert_eq!(record.seq, INSERT);
+    let hits: Vec<(&str, u64)> = trimmer.hits().map(|(a, n)| (a.name.as_str(), n)).collect();
+    assert_eq!(hits, vec![("TruSeq", 1), ("Nextera", 1), ("SmallRNA", 0)]);
+}
+
+#[test]
+fn test_trim_partial_adapter_with_mismatch() {
+    let mut trimmer = AdapterTrimmer::new(vec![Adapter::new("TruSeq", TRUSEQ)]);
+    let record = trimmer.trim(fastq(&format!("{}AGATCG", INSERT)));
+    assert_eq!(record.seq, INSERT);
+    // one mismatch in a full-length adapter
+    let record = trimmer.trim(fastq(&format!("{}AGATCGGTAGAGC", INSERT)));
+    assert_eq!(record.seq, INSERT);
+    // two mismatches exceed the budget of 13 bases
+    let record = trimmer.trim(fastq(&format!("{}AGTTCGGTAGAGC", INSERT)));
+    assert_eq!(record.seq.len(), INSERT.len() + 13);
+}
+
+#[test]
+fn test_trim_pair_by_overlap() {
+    let mut trimmer = AdapterTrimmer::new(Vec::new());
+    let read1 = format!("{}{}", INSERT, "ACGTTTGACCAGTA");
+    let read2 = format!("{}{}", revcomp_str(INSERT), "TTCAGGCATGCAAC");
+    let (record1, record2) = trimmer.trim_pair(fastq(&read1), fastq(&read2));
+    assert_eq!(record1.seq, INSERT);
+    assert_eq!(record2.seq, revcomp_str(INSERT));
+    assert_eq!(trimmer.overlap_hits(), 1);
+
+    // the insert is longer than the reads
+    let (record1, record2) =
+        trimmer.trim_pair(fastq(&INSERT[..20]), fastq(&revcomp_str(INSERT)[..20]));
+    assert_eq!(record1.seq.len(), 20);
+    assert_eq!(record2.seq.len(), 20);
+    assert_eq!(trimmer.overlap_hits(), 1);
+}
+
+#[test]
+fn test_trim_pair_without_overlap() {
+    let mut trimmer = AdapterTrimmer::new(Vec::new());
+    let read1 = format!("{}{}", INSERT, "ACGTTTGACCAGTA");
+    // the mates only overlap by the first 6 bases of read1 by chance
+    let read2 = revcomp_str("CCTAGGTTCAGCATGCAACTTGACCGTAAGCTTGCAAGGATTAC");
+    let (record1, record2) = trimmer.trim_pair(fastq(&read1), fastq(&read2));
+    assert_eq!(record1.seq, read1);
+    assert_eq!(record2.seq, read2);
+    assert_eq!(trimmer.overlap_hits(), 0);
+    assert_eq!(
+        trimmer.find_insert(read1.as_bytes(), read2.as_bytes()),
+        None
+    );
+    trimmer.min_pair_overlap = 4;
+    assert_eq!(
+        trimmer.find_insert(read1.as_bytes(), read2.as_bytes()),
+        Some(6)
+    );
+}
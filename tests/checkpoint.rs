@@ -0,0 +1,148 @@
+mod common;
+
+use common::{bgzip, temp_dir};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+static DATA: &str =
+    "@r1\nACGT\n+\nIIII\n@r2\nAC\nGT\n+\nII\nII\n\n@r3\nGGGG\n+\nIIII\n@r4\nTT\n+\nII\n";
+
+fn heads(records: &mut kseq::Paths) -> Vec<String> {
+    let mut heads = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        heads.push(record.head().to_string());
+    }
+    heads
+}
+
+// read 2 records, capture a checkpoint and resume from it
+fn check_resume(path: &PathBuf, bgzf: bool) {
+    let mut records = kseq::parse_path(path).unwrap();
+    assert!(records.detect_qual_encoding(1).unwrap().is_some());
+    // the virtual offset is unknown in the record put back
+    let checkpoint = records.checkpoint().unwrap();
+    assert_eq!((checkpoint.offset, checkpoint.virtual_offset), (0, None));
+    records.iter_record().unwrap().unwrap();
+    records.iter_record().unwrap().unwrap();
+    let checkpoint = records.checkpoint().unwrap();
+    assert_eq!((checkpoint.record_index, checkpoint.offset), (2, 34));
+    assert_eq!(checkpoint.virtual_offset.is_some(), bgzf);
+    assert_eq!(heads(&mut records), vec!["r3", "r4"]);
+
+    let mut records = kseq::resume_path(path, &checkpoint).unwrap();
+    assert_eq!(heads(&mut records), vec!["r3", "r4"]);
+    assert_eq!(records.checkpoint().unwrap().record_index, 4);
+    // resume without virtual offsets
+    let checkpoint = kseq::record::Checkpoint {
+        virtual_offset: None,
+        ..checkpoint
+    };
+    let mut records = kseq::resume_path(path, &checkpoint).unwrap();
+    assert_eq!(heads(&mut records), vec!["r3", "r4"]);
+}
+
+#[test]
+fn test_checkpoint_plain_and_gz() {
+    let dir = temp_dir("plain");
+    let plain = dir.join("a.fq");
+    fs::write(&plain, DATA).unwrap();
+    check_resume(&plain, false);
+
+    let gz = dir.join("a.fq.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA.as_bytes()).unwrap();
+    fs::write(&gz, encoder.finish().unwrap()).unwrap();
+    check_resume(&gz, false);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_bgzf() {
+    let dir = temp_dir("bgzf");
+    let path = dir.join("a.fq.gz");
+    fs::write(&path, bgzip(DATA.as_bytes(), 10)).unwrap();
+    check_resume(&path, true);
+
+    // a BGZF file is read as a gzip file
+    let mut records = kseq::parse_path(&path).unwrap();
+    assert_eq!(heads(&mut records), vec!["r1", "r2", "r3", "r4"]);
+    let mut reader = kseq::bgzf::BgzfReader::new(fs::File::open(&path).unwrap());
+    reader.seek_virtual(1 << 16).unwrap_err();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_checkpoint_fofn() {
+    let dir = temp_dir("fofn");
+    fs::write(dir.join("a.fq"), DATA).unwrap();
+    fs::write(dir.join("b.fq.gz"), bgzip(DATA.as_bytes(), 16)).unwrap();
+    let fofn = dir.join("all.fofn");
+    fs::write(&fofn, "a.fq\nb.fq.gz\n").unwrap();
+
+    let mut records = kseq::parse_path(&fofn).unwrap();
+    for _ in 0..5 {
+        records.iter_record().unwrap().unwrap();
+    }
+    let checkpoint = records.checkpoint().unwrap();
+    assert_eq!((checkpoint.file_index, checkpoint.record_index), (1, 5));
+    let mut records = kseq::resume_path(&fofn, &checkpoint).unwrap();
+    assert_eq!(heads(&mut records), vec!["r2", "r3", "r4"]);
+    assert_eq!(records.checkpoint().unwrap().record_index, 8);
+
+    let checkpoint = kseq::record::Checkpoint {
+        file_index: 2,
+        ..checkpoint
+    };
+    assert!(kseq::resume_path(&fofn, &checkpoint).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_bgzf_block_size() {
+    let mut data = bgzip(DATA.as_bytes(), 16);
+    // set the uncompressed size of the first block to 1 GB
+    let end = u16::from_le_bytes([data[16], data[17]]) as usize + 1;
+    data[end - 4..end].copy_from_slice(&(1u32 << 30).to_le_bytes());
+    let mut reader = kseq::bgzf::BgzfReader::new(&data[..]);
+    let err = std::io::Read::read_to_end(&mut reader, &mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_bgzf_corrupted() {
+    let read = |data: &[u8]| {
+        let mut reader = kseq::bgzf::BgzfReader::new(data);
+        let mut out = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut out).map(|_| out)
+    };
+    let data = bgzip(DATA.as_bytes(), 16);
+    assert_eq!(read(&data).unwrap(), DATA.as_bytes());
+    // multiple members, i.e. concatenated files with EOF blocks
+    let mut twice = data.clone();
+    twice.extend_from_slice(&data);
+    assert_eq!(read(&twice).unwrap(), DATA.repeat(2).as_bytes());
+    // a truncated block or header
+    assert!(read(&data[..data.len() - 30]).is_err());
+    assert!(read(&data[..5]).is_err());
+    // a bad BC subfield
+    let mut bad = data.clone();
+    bad[12] = b'X';
+    assert!(read(&bad).is_err());
+    // a bad checksum
+    let mut bad = data.clone();
+    let end = u16::from_le_bytes([data[16], data[17]]) as usize + 1;
+    bad[end - 8] ^= 1;
+    assert!(read(&bad).is_err());
+}
+
+#[test]
+fn test_bgzf_empty() {
+    let dir = temp_dir("empty");
+    let path = dir.join("empty.fq.gz");
+    fs::write(&path, bgzip(b"", 16)).unwrap();
+    assert!(kseq::parse_path(&path).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
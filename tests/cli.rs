@@ -0,0 +1,130 @@
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::temp_dir;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+static DATA: &str = "@r1 first\nACGTAC\n+\nIIIII#\n@r2\nggcc\n+\n!!!!\n@r3\nAAAA\n+\nIIII\n";
+
+fn kseq(args: &[&str], stdin: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_kseq"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_cli_stdin() {
+    assert_eq!(
+        kseq(&["fq2fa"], DATA),
+        ">r1 first\nACGTAC\n>r2\nggcc\n>r3\nAAAA\n"
+    );
+    assert_eq!(
+        kseq(&["head", "-n", "1", "-"], DATA),
+        "@r1 first\nACGTAC\n+\nIIIII#\n"
+    );
+    assert_eq!(
+        kseq(&["grep", "-p", "r2", "-p", "r3"], DATA)
+            .lines()
+            .count(),
+        8
+    );
+    assert_eq!(
+        kseq(&["grep", "-s", "-v", "-p", "AAAA"], DATA)
+            .lines()
+            .count(),
+        8
+    );
+    assert_eq!(
+        kseq(&["subseq", "-r", "2:3"], DATA),
+        "@r1 first\nCG\n+\nII\n@r2\ngc\n+\n!!\n@r3\nAA\n+\nII\n"
+    );
+    assert_eq!(
+        kseq(&["seq", "-r", "-u", "-m", "5"], DATA),
+        "@r1 first\nGTACGT\n+\n#IIIII\n"
+    );
+    assert_eq!(
+        kseq(&["rename", "-p", "read", "-D", "-o", "-"], DATA)
+            .lines()
+            .filter(|l| l.starts_with('@'))
+            .collect::<Vec<_>>(),
+        vec!["@read1", "@read2", "@read3"]
+    );
+    let stats = kseq(&["stats"], DATA);
+    assert!(stats.starts_with("file\tnum_seqs\t"));
+    assert!(stats.contains("\n-\t3\t14\t4\t"));
+}
+
+#[test]
+fn test_cli_fofn_and_split() {
+    let dir = temp_dir("split");
+    fs::write(dir.join("a.fq"), DATA).unwrap();
+    fs::write(dir.join("b.fq"), DATA).unwrap();
+    fs::write(dir.join("all.fofn"), "a.fq\n# comment\nb.fq\n").unwrap();
+    let fofn = dir.join("all.fofn");
+    let fofn = fofn.to_str().unwrap();
+    assert_eq!(kseq(&["fq2fa", fofn], "").lines().count(), 12);
+
+    let prefix = dir.join("part");
+    kseq(
+        &[
+            "split",
+            "-s",
+            "4",
+            "-p",
+            prefix.to_str().unwrap(),
+            "-g",
+            fofn,
+        ],
+        "",
+    );
+    let mut parts = Vec::new();
+    for i in 1..=2 {
+        let path = dir.join(format!("part.part_{:03}.fq.gz", i));
+        parts.push(kseq(&["fq2fa", path.to_str().unwrap()], "").lines().count() / 2);
+    }
+    assert_eq!(parts, vec![4, 2]);
+    assert!(!dir.join("part.part_003.fq.gz").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_subseq_bed() {
+    let dir = temp_dir("bed");
+    let bed = dir.join("a.bed");
+    fs::write(&bed, "r1\t1\t4\tx\t0\t-\nr3\t0\t2\n").unwrap();
+    assert_eq!(
+        kseq(&["subseq", "--bed", bed.to_str().unwrap()], DATA),
+        ">r1_2-4:- x\nACG\n>r3_1-2:.\nAA\n"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_fqidx() {
+    let dir = temp_dir("fqidx");
+    let input = dir.join("a.fq");
+    fs::write(&input, DATA).unwrap();
+    let input = input.to_str().unwrap();
+    kseq(&["fqidx", input], "");
+    assert!(dir.join("a.fq.fqi").exists());
+    assert_eq!(
+        kseq(&["fqidx", input, "r3", "r1"], ""),
+        "@r3\nAAAA\n+\nIIII\n@r1 first\nACGTAC\n+\nIIIII#\n"
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,63 @@
+// helpers shared by integration tests, each test crate only uses some of them
+#![allow(dead_code)]
+
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use kseq::record::OwnedFastx;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+
+// create a temporary directory of a test, which is removed by the test
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "kseq-{}-{}-{}",
+        env!("CARGO_CRATE_NAME"),
+        std::process::id(),
+        name
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// compress data into BGZF blocks of at most `block_size` bytes, followed by an EOF block
+pub fn bgzip(data: &[u8], block_size: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for block in data.chunks(block_size).chain(std::iter::once(&[][..])) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(block).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut crc = Crc::new();
+        crc.update(block);
+        let bsize = (18 + compressed.len() + 8 - 1) as u16;
+        out.extend_from_slice(&[
+            0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0,
+        ]);
+        out.extend_from_slice(&bsize.to_le_bytes());
+        out.extend_from_slice(&compressed);
+        out.extend_from_slice(&crc.sum().to_le_bytes());
+        out.extend_from_slice(&(block.len() as u32).to_le_bytes());
+    }
+    out
+}
+
+// parse records in memory
+pub fn records(data: &str) -> kseq::Paths<'static> {
+    kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap()
+}
+
+// a fastq record
+pub fn record(head: &str, des: &str, seq: &str, qual: &str) -> OwnedFastx {
+    OwnedFastx {
+        head: head.to_string(),
+        des: des.to_string(),
+        seq: seq.to_string(),
+        sep: "+".to_string(),
+        qual: qual.to_string(),
+    }
+}
+
+// a fastq record named `r` with the quality `I` of all bases
+pub fn fastq(seq: &str) -> OwnedFastx {
+    record("r", "", seq, &"I".repeat(seq.len()))
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::records;
+use kseq::dedup::{BloomFilter, Dedup};
+use kseq::writer::Writer;
+
+fn fasta(seqs: &[&str]) -> String {
+    seqs.iter()
+        .enumerate()
+        .map(|(i, s)| format!(">r{}\n{}\n", i, s))
+        .collect()
+}
+
+#[test]
+fn test_dedup_exact() {
+    let data = fasta(&["ACGT", "ACGT", "TTTT", "acgt", "TTTT"]);
+    let mut dedup = Dedup::exact();
+    let mut writer = Writer::new(Vec::new());
+    assert_eq!(dedup.run(&mut records(&data), &mut writer).unwrap(), 3);
+    assert_eq!(writer.into_inner(), b">r0\nACGT\n>r2\nTTTT\n>r3\nacgt\n");
+    assert_eq!((dedup.num_records(), dedup.num_duplicates()), (5, 2));
+    assert!((dedup.duplication_rate() - 0.4).abs() < 1e-9);
+}
+
+#[test]
+fn test_dedup_paired() {
+    let data1 = fasta(&["AAAA", "AAAA", "AAAA", "CCCC"]);
+    let data2 = fasta(&["GGGG", "GGGG", "TTTT", "GGGG"]);
+    let mut dedup = Dedup::exact();
+    let mut writer1 = Writer::new(Vec::new());
+    let mut writer2 = Writer::new(Vec::new());
+    let n = dedup
+        .run_paired(
+            &mut records(&data1),
+            &mut records(&data2),
+            &mut writer1,
+            &mut writer2,
+        )
+        .unwrap();
+    assert_eq!(n, 3);
+    assert_eq!(writer2.into_inner(), b">r0\nGGGG\n>r2\nTTTT\n>r3\nGGGG\n");
+
+    // the mates of a pair are not concatenated
+    let mut dedup = Dedup::exact();
+    let data1 = fasta(&["AA", "AAA"]);
+    let data2 = fasta(&["AAA", "AA"]);
+    let mut r1 = records(&data1);
+    let mut r2 = records(&data2);
+    let mut writer1 = Writer::new(Vec::new());
+    let mut writer2 = Writer::new(Vec::new());
+    assert_eq!(
+        dedup
+            .run_paired(&mut r1, &mut r2, &mut writer1, &mut writer2)
+            .unwrap(),
+        2
+    );
+}
+
+#[test]
+fn test_dedup_approximate() {
+    let mut bloom = BloomFilter::new(1000, 0.01).unwrap();
+    assert!(bloom.size() > 1000 && bloom.size() < 2000);
+    assert!(!bloom.insert(42));
+    assert!(bloom.insert(42));
+
+    let seqs: Vec<String> = (0..1000).map(|i| format!("ACGT{:010}", i)).collect();
+    let mut dedup = Dedup::approximate(1000, 0.01).unwrap();
+    let mut exact = Dedup::exact();
+    for seq in seqs.iter().chain(seqs.iter().take(100)) {
+        let record = kseq::record::OwnedFastx {
+            seq: seq.clone(),
+            ..Default::default()
+        };
+        dedup.is_duplicate(&record);
+        exact.is_duplicate(&record);
+    }
+    assert_eq!(exact.num_duplicates(), 100);
+    // all true duplicates are found, with a few false positives
+    assert!(dedup.num_duplicates() >= 100 && dedup.num_duplicates() < 130);
+}
+
+#[test]
+fn test_bloom_filter_params() {
+    for fp_rate in [0.0, 1.0, -0.1, 2.0, f64::NAN] {
+        assert!(BloomFilter::new(1000, fp_rate).is_err());
+        assert!(Dedup::approximate(1000, fp_rate).is_err());
+    }
+    // a high rate still gives a usable filter
+    let mut bloom = BloomFilter::new(1, 0.99).unwrap();
+    assert_eq!(bloom.size(), 8);
+    assert!(!bloom.insert(42));
+    assert!(bloom.insert(42));
+}
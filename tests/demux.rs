@@ -0,0 +1,143 @@
+mod common;
+
+use common::{records, temp_dir};
+use kseq::demux::{BarcodeLocation, Demultiplexer, SampleSheet};
+use std::fs;
+use std::path::Path;
+
+fn read(path: &Path) -> Vec<(String, String)> {
+    let mut records = kseq::parse_path(path).unwrap();
+    let mut out = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        out.push((record.head().to_string(), record.seq().to_string()));
+    }
+    out
+}
+
+fn sheet() -> SampleSheet {
+    let mut sheet = SampleSheet::new();
+    sheet.add("s1", "ACGT+AACC");
+    sheet.add("s2", "TGCA+GGTT");
+    sheet
+}
+
+#[test]
+fn test_sample_sheet() {
+    let dir = temp_dir("sheet");
+    let path = dir.join("sheet.csv");
+    fs::write(
+        &path,
+        "sample,barcode\n# comment\ns1,ACGT+AACC\n\ns2\tTGCAGGTT\n",
+    )
+    .unwrap();
+    assert_eq!(SampleSheet::from_path(&path).unwrap(), {
+        let mut sheet = SampleSheet::new();
+        sheet.add("s1", "ACGT+AACC");
+        sheet.add("s2", "TGCAGGTT");
+        sheet
+    });
+    fs::write(&path, "s1,ACGT\nbad line\n").unwrap();
+    assert!(SampleSheet::from_path(&path).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+
+    // collisions and barcodes of different lengths
+    let mut sheet = SampleSheet::new();
+    sheet.add("s1", "ACGTAC");
+    sheet.add("s2", "ACGTTT");
+    assert!(Demultiplexer::new(&sheet, BarcodeLocation::Header, 0).is_ok());
+    assert!(Demultiplexer::new(&sheet, BarcodeLocation::Header, 1).is_err());
+    sheet.add("s3", "ACG");
+    assert!(Demultiplexer::new(&sheet, BarcodeLocation::Header, 0).is_err());
+
+    // invalid sample names and barcodes
+    for (name, barcode) in [
+        ("s1", "TTTT+GGGG"),
+        ("Undetermined", "TTTT+GGGG"),
+        ("../s3", "TTTT+GGGG"),
+        ("", "TTTT+GGGG"),
+        ("s3", "TTTT+GGGN"),
+    ] {
+        let mut invalid = self::sheet();
+        invalid.add(name, barcode);
+        assert!(Demultiplexer::new(&invalid, BarcodeLocation::Header, 0).is_err());
+    }
+    let mut reserved = self::sheet();
+    reserved.add("Undetermined", "TTTT+GGGG");
+    let err = Demultiplexer::new(&reserved, BarcodeLocation::Header, 0)
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "reserved sample name: Undetermined");
+}
+
+#[test]
+fn test_demux_header() {
+    let dir = temp_dir("header");
+    let data = "@r1 1:N:0:ACGT+AACC\nAAAA\n+\nIIII\n@r2 1:N:0:TGCA+GGTA\nCCCC\n+\nIIII\n\
+                @r3 1:N:0:NNNN+NNNN\nGGGG\n+\nIIII\n@r4 1:N:0:ACGTAACN\nTTTT\n+\nIIII\n";
+    let mut demux = Demultiplexer::new(&sheet(), BarcodeLocation::Header, 1).unwrap();
+    let template = dir.join("{sample}.fq");
+    demux
+        .run(&mut records(data), None, template.to_str().unwrap())
+        .unwrap();
+    assert_eq!(
+        demux.counts(),
+        vec![("s1", 2), ("s2", 1), ("undetermined", 1)]
+    );
+    let heads =
+        |name: &str| -> Vec<String> { read(&dir.join(name)).into_iter().map(|(h, _)| h).collect() };
+    assert_eq!(heads("s1.fq"), vec!["r1", "r4"]);
+    assert_eq!(heads("s2.fq"), vec!["r2"]);
+    assert_eq!(heads("undetermined.fq"), vec!["r3"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_demux_read_start_and_index_read() {
+    let dir = temp_dir("inline");
+    let data = "@r1\nACGTAACCGGGG\n+\nIIIIIIIIIIII\n@r2\nTTTTTTTTGGGG\n+\nIIIIIIIIIIII\n";
+    let location = BarcodeLocation::ReadStart { trim: true };
+    let mut demux = Demultiplexer::new(&sheet(), location, 0).unwrap();
+    let template = dir.join("inline.{sample}.fq");
+    demux
+        .run(&mut records(data), None, template.to_str().unwrap())
+        .unwrap();
+    assert_eq!(
+        read(&dir.join("inline.s1.fq")),
+        vec![("r1".into(), "GGGG".into())]
+    );
+    assert_eq!(
+        read(&dir.join("inline.undetermined.fq")),
+        vec![("r2".into(), "TTTTTTTTGGGG".into())]
+    );
+
+    let data = "@r1\nAAAA\n+\nIIII\n@r2\nCCCC\n+\nIIII\n";
+    let index = "@r1\nTGCAGGTT\n+\nIIIIIIII\n@r2\nACGTAACC\n+\nIIIIIIII\n";
+    let mut demux = Demultiplexer::new(&sheet(), BarcodeLocation::IndexRead, 0).unwrap();
+    let template = dir.join("index.{sample}.fq");
+    demux
+        .run(
+            &mut records(data),
+            Some(&mut records(index)),
+            template.to_str().unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        read(&dir.join("index.s1.fq")),
+        vec![("r2".into(), "CCCC".into())]
+    );
+    assert_eq!(
+        read(&dir.join("index.s2.fq")),
+        vec![("r1".into(), "AAAA".into())]
+    );
+
+    // extra records of the index read file
+    let index = format!("{}@r3\nTGCAGGTT\n+\nIIIIIIII\n", index);
+    assert!(demux
+        .run(
+            &mut records(data),
+            Some(&mut records(&index)),
+            template.to_str().unwrap(),
+        )
+        .is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,98 @@
+#[cfg(feature = "regex")]
+use kseq::filter::Regex;
+use kseq::filter::{Filter, IdSet, Match, Motif, Strand};
+use kseq::writer::Writer;
+use std::io::Cursor;
+
+static DATA: &str = ">r1 sample=A\nACGTTGCA\n>r2 sample=B\nTTTTTTTT\n>r3 sample=A\nGGGAAACCC\n";
+
+fn records() -> kseq::Paths<'static> {
+    kseq::parse_reader(Cursor::new(DATA.as_bytes().to_vec())).unwrap()
+}
+
+#[test]
+fn test_filter_ids() {
+    let path = std::env::temp_dir().join(format!("kseq-ids-{}.txt", std::process::id()));
+    std::fs::write(&path, "# ids\n>r3 extra\n\nr1\n").unwrap();
+    let ids = IdSet::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains("r1") && ids.contains("r3") && !ids.contains("r2"));
+
+    let hits = Filter::Ids(ids).grep(&mut records()).unwrap();
+    let heads: Vec<&str> = hits.iter().map(|(r, _)| r.head.as_str()).collect();
+    assert_eq!(heads, vec!["r1", "r3"]);
+    assert_eq!(
+        hits[0].1[0],
+        Match {
+            start: 0,
+            end: 2,
+            strand: Strand::Forward,
+            mismatches: 0
+        }
+    );
+
+    let mut writer = Writer::new(Vec::new());
+    let filter = Filter::Ids(["r1", "r3"].iter().collect());
+    assert_eq!(filter.run(&mut records(), &mut writer, true).unwrap(), 1);
+    assert_eq!(writer.into_inner(), b">r2 sample=B\nTTTTTTTT\n");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_filter_regex() {
+    let filter = Filter::Des(Regex::new(r"sample=A").unwrap());
+    let hits = filter.grep(&mut records()).unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!((hits[1].1[0].start, hits[1].1[0].end), (1, 9));
+
+    let filter = Filter::Head(Regex::new(r"^r[12]$").unwrap());
+    assert_eq!(filter.grep(&mut records()).unwrap().len(), 2);
+}
+
+#[test]
+fn test_motif() {
+    // TTTC is found on the reverse strand as GAAA
+    let motif = Motif::new("TTTC", 0).unwrap();
+    let matches = motif.find(b"GGGAAACCC");
+    assert_eq!(
+        matches,
+        vec![Match {
+            start: 2,
+            end: 6,
+            strand: Strand::Reverse,
+            mismatches: 0
+        }]
+    );
+
+    // IUPAC codes in the motif, lower case sequences
+    let mut motif = Motif::new("GGRAAAN", 0).unwrap();
+    motif.both_strands = false;
+    assert_eq!(motif.find(b"gggaaaccc").len(), 1);
+    // an ambiguous base of a sequence only matches a code covering it
+    assert!(motif.find(b"GGNAAAC").is_empty());
+
+    // mismatches
+    let motif = Motif::new("TTTTTT", 1).unwrap();
+    let matches = motif.find(b"GGGAAACCC");
+    assert!(matches.is_empty());
+    let motif = Motif::new("GGGATA", 1).unwrap();
+    let matches = motif.find(b"GGGAAACCC");
+    assert_eq!(
+        matches,
+        vec![Match {
+            start: 0,
+            end: 6,
+            strand: Strand::Forward,
+            mismatches: 1
+        }]
+    );
+
+    // a palindromic motif is reported once
+    assert_eq!(Motif::new("ACGT", 0).unwrap().find(b"TACGTA").len(), 1);
+
+    // characters other than IUPAC codes are rejected
+    assert!(Motif::new("ACGX", 0).is_err());
+    assert!(Motif::new("AC-T", 0).is_err());
+    assert!(Motif::new("ACGé", 0).is_err());
+}
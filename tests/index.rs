@@ -0,0 +1,111 @@
+mod common;
+
+use common::{bgzip, temp_dir};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use kseq::index::{FastqIndex, IndexedReader};
+use std::fs;
+use std::io::Write;
+use std::time::{Duration, SystemTime};
+
+static DATA: &str =
+    "@r1 first\nACGT\n+\nIIII\n@r2\nAC\nGT\n+\nII\nI#\n\n@r3\nGGGG\n+\n!!!!\n@r4\nTT\n+\nII\n";
+
+// fetch records in a random order
+fn check_fetch(reader: &mut IndexedReader) {
+    let r2 = reader.fetch("r2").unwrap().unwrap();
+    assert_eq!((r2.seq.as_str(), r2.qual.as_str()), ("ACGT", "III#"));
+    let r1 = reader.fetch("r1").unwrap().unwrap();
+    assert_eq!((r1.des.as_str(), r1.seq.as_str()), (" first", "ACGT"));
+    let r4 = reader.fetch("r4").unwrap().unwrap();
+    assert_eq!((r4.seq.as_str(), r4.qual.as_str()), ("TT", "II"));
+    assert_eq!(reader.fetch("r3").unwrap().unwrap().qual, "!!!!");
+    assert!(reader.fetch("r5").unwrap().is_none());
+}
+
+#[test]
+fn test_index_plain() {
+    let dir = temp_dir("plain");
+    let path = dir.join("a.fq");
+    fs::write(&path, DATA).unwrap();
+    let index = FastqIndex::build(&path).unwrap();
+    assert_eq!(index.len(), 4);
+    let r2 = index.get("r2").unwrap();
+    assert_eq!((r2.offset, r2.length, r2.virtual_offset), (22, 18, None));
+
+    // save and load the index
+    let index_path = FastqIndex::index_path(&path);
+    assert_eq!(index_path, dir.join("a.fq.fqi"));
+    index.save(&index_path).unwrap();
+    assert!(fs::read_to_string(&index_path)
+        .unwrap()
+        .starts_with("r1\t0\t22\nr2\t22\t18\n"));
+    assert_eq!(FastqIndex::load(&index_path).unwrap(), index);
+    check_fetch(&mut IndexedReader::open(&path).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_index_bgzf() {
+    let dir = temp_dir("bgzf");
+    let path = dir.join("a.fq.gz");
+    fs::write(&path, bgzip(DATA.as_bytes(), 7)).unwrap();
+    let index = FastqIndex::build(&path).unwrap();
+    assert!(index.records().iter().all(|r| r.virtual_offset.is_some()));
+    check_fetch(&mut IndexedReader::new(&path, index.clone()).unwrap());
+
+    // an index of the plain file has no virtual offsets
+    let plain = dir.join("a.fq");
+    fs::write(&plain, DATA).unwrap();
+    let mut reader = IndexedReader::new(&path, FastqIndex::build(&plain).unwrap()).unwrap();
+    assert!(reader.fetch("r1").is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_index_errors() {
+    let dir = temp_dir("errors");
+    let gz = dir.join("a.fq.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA.as_bytes()).unwrap();
+    fs::write(&gz, encoder.finish().unwrap()).unwrap();
+    assert!(FastqIndex::build(&gz).is_err());
+    assert!(IndexedReader::new(&gz, FastqIndex::new()).is_err());
+
+    let dup = dir.join("dup.fq");
+    fs::write(&dup, format!("{}@r1\nA\n+\nI\n", DATA)).unwrap();
+    assert!(FastqIndex::build(&dup).is_err());
+
+    // a stale index
+    let path = dir.join("a.fq");
+    fs::write(&path, DATA).unwrap();
+    let index = FastqIndex::build(&path).unwrap();
+    fs::write(&path, &DATA[1..]).unwrap();
+    assert!(IndexedReader::new(&path, index)
+        .unwrap()
+        .fetch("r2")
+        .is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_index_outdated() {
+    let dir = temp_dir("outdated");
+    let path = dir.join("a.fq");
+    fs::write(&path, DATA).unwrap();
+    let index_path = FastqIndex::index_path(&path);
+    FastqIndex::build(&path).unwrap().save(&index_path).unwrap();
+
+    // the file is changed after its index is saved
+    fs::write(&path, format!("@r0\nA\n+\nI\n{}", DATA)).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+    let mut reader = IndexedReader::open(&path).unwrap();
+    assert_eq!(reader.index().len(), 5);
+    check_fetch(&mut reader);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,79 @@
+use kseq::kmer::{KmerInt, KmerIter, MinimizerIter};
+use kseq::seq::revcomp;
+use std::collections::BTreeSet;
+
+// a deterministic pseudo-random sequence
+fn random_seq(len: usize) -> Vec<u8> {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            b"ACGT"[(state >> 32) as usize % 4]
+        })
+        .collect()
+}
+
+#[test]
+fn test_forward_kmers() {
+    let kmers: Vec<(usize, u64)> = KmerIter::new(b"acgtNAC", 2).collect();
+    assert_eq!(
+        kmers,
+        vec![(0, 0b0001), (1, 0b0110), (2, 0b1011), (5, 0b0001)]
+    );
+    let kmers: Vec<(usize, u64)> = KmerIter::new(b"ACN", 3).collect();
+    assert!(kmers.is_empty());
+}
+
+#[test]
+fn test_canonical_kmers() {
+    let seq = random_seq(500);
+    let rc = revcomp(&seq);
+    for &k in &[5, 31, 32] {
+        let fwd: BTreeSet<u64> = KmerIter::canonical(&seq, k).map(|x| x.1).collect();
+        let rev: BTreeSet<u64> = KmerIter::canonical(&rc, k).map(|x| x.1).collect();
+        assert_eq!(fwd, rev);
+    }
+    for &k in &[33, 64] {
+        let fwd: Vec<(usize, u128)> = KmerIter::canonical(&seq, k).collect();
+        let mut rev: Vec<(usize, u128)> = KmerIter::canonical(&rc, k)
+            .map(|(pos, kmer)| (seq.len() - k - pos, kmer))
+            .collect();
+        rev.reverse();
+        assert_eq!(fwd.len(), seq.len() - k + 1);
+        assert_eq!(fwd, rev);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_kmer_too_long() {
+    let _ = KmerIter::<u64>::new(b"ACGT", 33);
+}
+
+#[test]
+fn test_minimizers() {
+    let (w, k) = (10, 15);
+    let mut seq = random_seq(300);
+    seq[150] = b'N';
+    let mask = KmerIter::<u64>::new(&seq, k).mask();
+    let kmers: Vec<(usize, u64)> = KmerIter::canonical(&seq, k).collect();
+
+    // the minimizers of every window of w consecutive k-mers, by brute force
+    let mut expected = Vec::new();
+    for window in kmers.windows(w) {
+        if window[w - 1].0 - window[0].0 != w - 1 {
+            continue;
+        }
+        let min = window
+            .iter()
+            .min_by_key(|(_, kmer)| kmer.hash(mask))
+            .unwrap();
+        if expected.last() != Some(min) {
+            expected.push(*min);
+        }
+    }
+    let minimizers: Vec<(usize, u64)> = MinimizerIter::new(&seq, w, k).collect();
+    assert_eq!(minimizers, expected);
+}
@@ -0,0 +1,68 @@
+use kseq::normalize::{Format, Normalizer};
+use kseq::writer::Writer;
+use std::io::Cursor;
+
+static FASTQ: &str =
+    "@r1 first\r\nacgt\r\nACGT\r\n+r1 first\r\nIIII\r\n####\r\n@r2\nTTTT\n+\n!!!!\n";
+static FASTA: &str = ">r1 first\nAAAAA\nCC\ngGG\n>r2\r\nACGT\r\n";
+
+fn normalize(data: &str, normalizer: &Normalizer) -> String {
+    let mut records = kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap();
+    let mut writer = Writer::new(Vec::new());
+    assert_eq!(normalizer.run(&mut records, &mut writer).unwrap(), 2);
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_normalize_auto() {
+    let normalizer = Normalizer::default();
+    assert_eq!(
+        normalize(FASTQ, &normalizer),
+        "@r1 first\nacgtACGT\n+\nIIII####\n@r2\nTTTT\n+\n!!!!\n"
+    );
+    assert_eq!(
+        normalize(FASTA, &normalizer),
+        ">r1 first\nAAAAACCgGG\n>r2\nACGT\n"
+    );
+
+    let normalizer = Normalizer {
+        uppercase: true,
+        keep_sep: true,
+        line_width: 4,
+        ..Default::default()
+    };
+    assert_eq!(
+        normalize(FASTQ, &normalizer),
+        "@r1 first\nACGT\nACGT\n+r1 first\nIIII\n####\n@r2\nTTTT\n+\n!!!!\n"
+    );
+}
+
+#[test]
+fn test_normalize_to_fastq() {
+    let normalizer = Normalizer {
+        format: Format::Fastq,
+        fake_qual: b'5',
+        ..Default::default()
+    };
+    assert_eq!(
+        normalize(FASTA, &normalizer),
+        "@r1 first\nAAAAACCgGG\n+\n5555555555\n@r2\nACGT\n+\n5555\n"
+    );
+}
+
+#[test]
+fn test_normalize_to_fasta() {
+    let normalizer = Normalizer {
+        format: Format::Fasta,
+        line_width: 6,
+        ..Default::default()
+    };
+    assert_eq!(
+        normalize(FASTQ, &normalizer),
+        ">r1 first\nacgtAC\nGT\n>r2\nTTTT\n"
+    );
+    assert_eq!(
+        normalize(FASTA, &normalizer),
+        ">r1 first\nAAAAAC\nCgGG\n>r2\nACGT\n"
+    );
+}
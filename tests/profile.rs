@@ -0,0 +1,103 @@
+use kseq::profile::Profile;
+use std::io::Cursor;
+
+static DATA: &str =
+    "@1\nACGT\n+\nIIII\n@2\nACGG\n+\n+5?I\n@3\nACGTNA\n+\n!!!!!!\n@4\nACGT\n+\nIIII\n";
+
+fn profile(data: &str) -> Profile {
+    let mut profile = Profile::new();
+    let mut records = kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap();
+    while let Some(record) = records.iter_record().unwrap() {
+        profile.update(&record);
+    }
+    profile
+}
+
+#[test]
+fn test_qual_boxes() {
+    let boxes = profile(DATA).qual_boxes();
+    assert_eq!(boxes.len(), 6);
+    assert_eq!(boxes[0].mean, 22.5);
+    assert_eq!(boxes[0].median, 10);
+    assert_eq!(boxes[0].lower_quartile, 0);
+    assert_eq!(boxes[0].upper_quartile, 40);
+    assert_eq!(boxes[0].percentile_90, 40);
+    assert_eq!(boxes[3].median, 40);
+    assert_eq!(boxes[5].mean, 0.0);
+}
+
+#[test]
+fn test_composition_and_distributions() {
+    let profile = profile(DATA);
+    assert_eq!(profile.num_seqs(), 4);
+    let composition = profile.base_composition();
+    assert_eq!(composition[0], [100.0, 0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(composition[3], [0.0, 0.0, 25.0, 75.0, 0.0]);
+    assert_eq!(composition[4], [0.0, 0.0, 0.0, 0.0, 100.0]);
+    let lengths: Vec<(usize, u64)> = profile
+        .length_distribution()
+        .iter()
+        .map(|(&l, &c)| (l, c))
+        .collect();
+    assert_eq!(lengths, vec![(4, 3), (6, 1)]);
+    let gc = profile.gc_distribution();
+    assert_eq!(gc.len(), 101);
+    assert_eq!(gc[50], 2);
+    assert_eq!(gc[75], 1);
+    assert_eq!(gc[40], 1);
+}
+
+#[test]
+fn test_duplication() {
+    let profile = profile(DATA);
+    assert_eq!(profile.duplication_levels()[..3], [2, 1, 0]);
+    assert_eq!(profile.dedup_remaining(), 75.0);
+}
+
+#[test]
+fn test_merge_profile() {
+    let whole = profile(DATA);
+    let mut merged = profile("@1\nACGT\n+\nIIII\n@2\nACGG\n+\n+5?I\n");
+    merged.merge(&profile("@3\nACGTNA\n+\n!!!!!!\n@4\nACGT\n+\nIIII\n"));
+    assert_eq!(merged.qual_boxes(), whole.qual_boxes());
+    assert_eq!(merged.base_composition(), whole.base_composition());
+    assert_eq!(merged.duplication_levels(), whole.duplication_levels());
+}
+
+#[test]
+fn test_write_profile() {
+    let profile = profile(DATA);
+    let report = profile.report();
+    assert_eq!(report.num_seqs, 4);
+    assert_eq!(report.per_position_quality[0].median, 10);
+    assert_eq!(report.per_position_base_composition[4][4], 100.0);
+    assert_eq!(report.length_distribution[&6], 1);
+    assert_eq!(report.gc_distribution[50], 2);
+    assert_eq!(report.duplication_levels[1], 1);
+    assert_eq!(report.dedup_remaining, 75.0);
+
+    let mut tsv = Vec::new();
+    profile.write_tsv(&mut tsv).unwrap();
+    let tsv = String::from_utf8(tsv).unwrap();
+    assert!(tsv.contains(">>per_position_quality\n#position\tmean"));
+    assert!(tsv.contains("\n1\t22.50\t10\t0\t40\t0\t40\n"));
+    assert!(tsv.contains(">>length_distribution\n#length\tcount\n4\t3\n6\t1\n"));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_write_profile_json() {
+    let profile = profile(DATA);
+    let mut json = Vec::new();
+    profile.write_json(&mut json).unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(value["num_seqs"], 4);
+    assert_eq!(value["per_position_quality"][0]["median"], 10);
+    assert_eq!(value["per_position_base_composition"][4][4], 100.0);
+    assert_eq!(value["length_distribution"]["6"], 1);
+    assert_eq!(value["gc_distribution"][50], 2);
+    assert_eq!(value["duplication_levels"][1], 1);
+    assert_eq!(value["dedup_remaining"], 75.0);
+    let parsed: kseq::profile::Report = serde_json::from_slice(&json).unwrap();
+    assert_eq!(parsed, profile.report());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::temp_dir;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::io::{Cursor, Write};
+
+static DATA: &str = "@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nIIII\n>r3\nACGT\nACGT\n";
+
+fn read_all(records: &mut kseq::Paths) {
+    while records.iter_record().unwrap().is_some() {}
+}
+
+#[test]
+fn test_progress_file() {
+    let dir = temp_dir("file");
+    let plain = dir.join("a.fq");
+    fs::write(&plain, DATA).unwrap();
+    let mut records = kseq::parse_path(&plain).unwrap();
+    let progress = records.progress();
+    assert_eq!((progress.total_bytes, progress.num_records), (46, 0));
+    records.iter_record().unwrap().unwrap();
+    let progress = records.progress();
+    assert_eq!((progress.uncompressed_bytes, progress.num_records), (16, 1));
+    read_all(&mut records);
+    let progress = records.progress();
+    assert_eq!(progress.bytes_read, 46);
+    assert_eq!(progress.uncompressed_bytes, 46);
+    assert_eq!(progress.num_records, 3);
+    assert_eq!(progress.fraction(), Some(1.0));
+
+    let gz = dir.join("a.fq.gz");
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(DATA.as_bytes()).unwrap();
+    fs::write(&gz, encoder.finish().unwrap()).unwrap();
+    let size = fs::metadata(&gz).unwrap().len();
+    let fofn = dir.join("all.fofn");
+    fs::write(&fofn, "a.fq\na.fq.gz\n").unwrap();
+    let mut records = kseq::parse_path(&fofn).unwrap();
+    read_all(&mut records);
+    let progress = records.progress();
+    assert_eq!(progress.bytes_read, 46 + size);
+    assert_eq!(progress.total_bytes, 46 + size);
+    assert_eq!(progress.uncompressed_bytes, 92);
+    assert_eq!(progress.num_records, 6);
+
+    // the first file is counted as read after resuming in the second file
+    let mut records = kseq::parse_path(&fofn).unwrap();
+    for _ in 0..4 {
+        records.iter_record().unwrap().unwrap();
+    }
+    let checkpoint = records.checkpoint().unwrap();
+    let mut records = kseq::resume_path(&fofn, &checkpoint).unwrap();
+    assert!(records.progress().bytes_read >= 46);
+    read_all(&mut records);
+    let progress = records.progress();
+    assert_eq!(progress.bytes_read, progress.total_bytes);
+    assert_eq!(progress.num_records, 6);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_progress_reader() {
+    let mut records = kseq::parse_reader(Cursor::new(DATA.as_bytes().to_vec())).unwrap();
+    read_all(&mut records);
+    let progress = records.progress();
+    assert_eq!((progress.bytes_read, progress.total_bytes), (0, 0));
+    assert_eq!((progress.uncompressed_bytes, progress.num_records), (46, 3));
+    assert_eq!(progress.fraction(), None);
+}
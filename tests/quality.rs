@@ -0,0 +1,85 @@
+use kseq::quality::{self, QualEncoding};
+use std::io::Cursor;
+
+#[test]
+fn test_qual_scores() {
+    let data = b"@1\nACGT\n+\n!+5I\n".to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.qual_scores(33).collect::<Vec<_>>(), vec![0, 10, 20, 40]);
+    assert_eq!(record.mean_qual(33), Some(17.5));
+    assert_eq!(record.median_qual(33), Some(15.0));
+    let probs: Vec<f64> = record.error_probs(33).collect();
+    assert!((probs[1] - 0.1).abs() < 1e-12);
+    assert!((record.expected_errors(33) - 1.1101).abs() < 1e-12);
+}
+
+#[test]
+fn test_qual_of_fasta() {
+    let data = b">1\nACGT\n".to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.mean_qual(33), None);
+    assert_eq!(record.median_qual(33), None);
+    assert_eq!(record.expected_errors(33), 0.0);
+}
+
+#[test]
+fn test_qual_median() {
+    assert_eq!(quality::median(b"IIII!", 33), Some(40.0));
+    assert_eq!(quality::median(b"h", 64), Some(40.0));
+    assert_eq!(quality::median(b"", 33), None);
+}
+
+#[test]
+fn test_detect_qual_encoding() {
+    let cases: [(&str, Option<QualEncoding>); 4] = [
+        ("@1\nACGT\n+\n#5?I\n@2\nACGT\n+\nIIII\n", Some(QualEncoding::Sanger)),
+        ("@1\nACGT\n+\nBJTh\n@2\nACGT\n+\nhhhh\n", Some(QualEncoding::Illumina13)),
+        ("@1\nACGT\n+\n;@Th\n@2\nACGT\n+\nhhhh\n", Some(QualEncoding::Solexa)),
+        (">1\nACGT\n>2\nACGT\n", None),
+    ];
+    for (data, encoding) in cases.iter() {
+        let mut records = kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap();
+        assert_eq!(records.detect_qual_encoding(1000).unwrap(), *encoding);
+        // the records used for the detection are still available
+        let mut n = 0;
+        while let Some(record) = records.iter_record().unwrap() {
+            assert_eq!(record.seq(), "ACGT");
+            n += 1;
+        }
+        assert_eq!(n, 2);
+    }
+}
+
+#[test]
+fn test_detect_qual_encoding_first_records() {
+    let data = b"@1\nACGT\n+\nhhhh\n@2 r2\nAC\nGT\n+\nhh\nhh\n@3\nACGT\n+\n!!!!\n".to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    assert_eq!(
+        records.detect_qual_encoding(2).unwrap(),
+        Some(QualEncoding::Illumina13)
+    );
+    let heads: Vec<String> = (0..3)
+        .map(|_| {
+            let record = records.iter_record().unwrap().unwrap();
+            format!("{}{}:{}", record.head(), record.des(), record.qual())
+        })
+        .collect();
+    assert_eq!(heads, vec!["1:hhhh", "2 r2:hhhh", "3:!!!!"]);
+    assert!(records.iter_record().unwrap().is_none());
+}
+
+#[test]
+fn test_solexa_scores() {
+    assert_eq!(quality::solexa_to_phred(-5), 1);
+    assert_eq!(quality::solexa_to_phred(0), 3);
+    assert_eq!(quality::solexa_to_phred(10), 10);
+    assert_eq!(quality::solexa_to_phred(40), 40);
+    let scores: Vec<u8> = QualEncoding::Solexa.scores(b";@Jh").collect();
+    assert_eq!(scores, vec![1, 3, 10, 40]);
+    let scores: Vec<u8> = QualEncoding::Illumina13.scores(b"@Jh").collect();
+    assert_eq!(scores, vec![0, 10, 40]);
+    let scores: Vec<u8> = QualEncoding::Sanger.scores(b"!+I").collect();
+    assert_eq!(scores, vec![0, 10, 40]);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use common::temp_dir;
+use kseq::filter::Strand;
+use kseq::region::{Region, Regions};
+use kseq::writer::Writer;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+static FASTA: &str = ">chr1 first\nAACCGG\nTTAA\n>chr2\nACGTACGT\n>chr3\nGGGG\n";
+
+fn temp_file(dir: &Path, name: &str, content: &str) -> PathBuf {
+    let path = dir.join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+fn extract(regions: &Regions) -> String {
+    let mut records = kseq::parse_reader(Cursor::new(FASTA.as_bytes().to_vec())).unwrap();
+    let mut writer = Writer::new(Vec::new());
+    regions.run(&mut records, &mut writer).unwrap();
+    String::from_utf8(writer.into_inner()).unwrap()
+}
+
+#[test]
+fn test_region_extract() {
+    let mut regions = Regions::new();
+    regions.add(Region::new("chr1", 0, 4, None));
+    regions.add(Region::new("chr1", 6, 100, Some(Strand::Forward)));
+    regions.add(Region::new("chr2", 0, 3, Some(Strand::Reverse)));
+    // out of the sequence
+    regions.add(Region::new("chr3", 4, 8, None));
+    regions.add(Region::new("chr4", 0, 8, None));
+    assert_eq!(regions.len(), 5);
+    assert_eq!(
+        extract(&regions),
+        ">chr1_1-4:.\nAACC\n>chr1_7-10:+\nTTAA\n>chr2_1-3:-\nCGT\n"
+    );
+}
+
+#[test]
+fn test_region_bed() {
+    let bed =
+        "track name=test\n# comment\nchr2\t2\t6\tfeat1\t0\t-\nchr1\t1\t3\n\nchr3\t0\t2\t.\t0\t+\n";
+    let dir = temp_dir("bed");
+    let regions = Regions::from_bed(temp_file(&dir, "a.bed", bed)).unwrap();
+    assert_eq!(regions.len(), 3);
+    assert_eq!(
+        extract(&regions),
+        ">chr1_2-3:.\nAC\n>chr2_3-6:- feat1\nGTAC\n>chr3_1-2:+\nGG\n"
+    );
+    // space-delimited
+    let regions = Regions::from_bed(temp_file(&dir, "s.bed", "chr2 2  6 feat1 0 -\n")).unwrap();
+    assert_eq!(extract(&regions), ">chr2_3-6:- feat1\nGTAC\n");
+    // only the first field is matched against track and browser
+    let bed = "track\tname=test\ntrack_1\t0\t2\nbrowser_2 0 2\n";
+    let regions = Regions::from_bed(temp_file(&dir, "t.bed", bed)).unwrap();
+    assert_eq!(regions.len(), 2);
+    assert!(Regions::from_bed(temp_file(&dir, "b.bed", "chr1\t5\t3\n")).is_err());
+    assert!(Regions::from_bed(temp_file(&dir, "c.bed", "chr1\t5\n")).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_region_gff() {
+    let gff = "##gff-version 3\n\
+        chr1\tsrc\tgene\t3\t6\t.\t+\t.\tID=g1;Name=geneA\n\
+        chr1\tsrc\texon\t3\t4\t.\t+\t.\tID=e1\n\
+        chr2\tsrc\tgene\t1\t4\t.\t-\t.\tID=g2\n\
+        ##FASTA\n>chr1\nACGT\n";
+    let dir = temp_dir("gff");
+    let path = temp_file(&dir, "a.gff", gff);
+    let regions = Regions::from_gff(&path, Some("gene")).unwrap();
+    assert_eq!(
+        extract(&regions),
+        ">chr1_3-6:+ geneA\nCCGG\n>chr2_1-4:- g2\nACGT\n"
+    );
+    assert_eq!(Regions::from_gff(&path, None).unwrap().len(), 3);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_region_non_ascii() {
+    let region = Region::new("chr1", 1, 3, Some(Strand::Reverse));
+    assert_eq!(region.extract("AACéG").unwrap().seq, "GT");
+    // a multi-byte character in the region or across its boundaries
+    assert!(region.extract("AéCG").is_none());
+    assert!(region.extract("éACG").is_none());
+}
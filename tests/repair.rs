@@ -0,0 +1,77 @@
+mod common;
+
+use common::records;
+use kseq::repair::{mate_name, RepairStats, Repairer};
+use kseq::writer::Writer;
+
+fn fastq(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("@{}\nACGT\n+\nIIII\n", name))
+        .collect()
+}
+
+// run a Repairer, return the stats and the four outputs
+fn repair(repairer: &Repairer, data1: &str, data2: &str) -> (RepairStats, Vec<String>) {
+    let mut writers: Vec<_> = (0..4).map(|_| Writer::new(Vec::new())).collect();
+    let (pairs, orphans) = writers.split_at_mut(2);
+    let (out1, out2) = pairs.split_at_mut(1);
+    let (orphans1, orphans2) = orphans.split_at_mut(1);
+    let stats = repairer
+        .run(
+            &mut records(data1),
+            &mut records(data2),
+            &mut out1[0],
+            &mut out2[0],
+            &mut orphans1[0],
+            &mut orphans2[0],
+        )
+        .unwrap();
+    let outputs = writers
+        .into_iter()
+        .map(|w| String::from_utf8(w.into_inner()).unwrap())
+        .collect();
+    (stats, outputs)
+}
+
+#[test]
+fn test_mate_name() {
+    assert_eq!(mate_name("r1/1"), "r1");
+    assert_eq!(mate_name("r1/2"), "r1");
+    assert_eq!(mate_name("r1/3"), "r1/3");
+    assert_eq!(mate_name("r1"), "r1");
+}
+
+#[test]
+fn test_repair_in_memory() {
+    let data1 = fastq(&["a/1", "b/1", "c/1", "d/1"]);
+    let data2 = fastq(&["d/2", "e/2", "b/2", "a/2"]);
+    let (stats, outputs) = repair(&Repairer::in_memory(), &data1, &data2);
+    assert_eq!(
+        stats,
+        RepairStats {
+            pairs: 3,
+            orphans1: 1,
+            orphans2: 1
+        }
+    );
+    assert_eq!(outputs[0], fastq(&["b/1", "d/1", "a/1"]));
+    assert_eq!(outputs[1], fastq(&["b/2", "d/2", "a/2"]));
+    assert_eq!(outputs[2], fastq(&["c/1"]));
+    assert_eq!(outputs[3], fastq(&["e/2"]));
+}
+
+#[test]
+fn test_repair_on_disk() {
+    // names which sort differently with and without the mate suffix
+    let data1 = fastq(&["r1.5/1", "r2/1", "r1/1", "r3/1"]);
+    let data2 = fastq(&["r3/2", "r1/2", "r4/2", "r1.5/2"]);
+    let mut repairer = Repairer::on_disk(1);
+    repairer.set_tmp_dir(std::env::temp_dir());
+    let (stats, outputs) = repair(&repairer, &data1, &data2);
+    assert_eq!((stats.pairs, stats.orphans1, stats.orphans2), (3, 1, 1));
+    assert_eq!(outputs[0], fastq(&["r1/1", "r1.5/1", "r3/1"]));
+    assert_eq!(outputs[1], fastq(&["r1/2", "r1.5/2", "r3/2"]));
+    assert_eq!(outputs[2], fastq(&["r2/1"]));
+    assert_eq!(outputs[3], fastq(&["r4/2"]));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::records;
+use kseq::sample::Sampler;
+use kseq::writer::Writer;
+use std::fs;
+use std::path::PathBuf;
+
+fn fastq(n: usize, mate: u8) -> String {
+    (0..n)
+        .map(|i| format!("@r{}/{}\nACGT\n+\nIIII\n", i, mate))
+        .collect()
+}
+
+fn temp_file(name: &str, data: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("kseq-sample-{}-{}", std::process::id(), name));
+    fs::write(&path, data).unwrap();
+    path
+}
+
+fn ids(data: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .filter(|l| l.starts_with('@'))
+        .map(|l| l[1..].split('/').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_sample_fraction() {
+    let data = fastq(1000, 1);
+    let sample = |seed| {
+        let mut writer = Writer::new(Vec::new());
+        let n = Sampler::new(seed)
+            .fraction(&mut records(&data), 0.1, &mut writer)
+            .unwrap();
+        let data = writer.into_inner();
+        assert_eq!(n as usize, ids(&data).len());
+        data
+    };
+    let first = sample(11);
+    assert_eq!(first, sample(11));
+    assert_ne!(first, sample(12));
+    let n = ids(&first).len();
+    assert!(n > 50 && n < 150);
+
+    let (data1, data2) = (fastq(100, 1), fastq(100, 2));
+    let mut writer1 = Writer::new(Vec::new());
+    let mut writer2 = Writer::new(Vec::new());
+    Sampler::default()
+        .fraction_paired(
+            &mut records(&data1),
+            &mut records(&data2),
+            0.5,
+            &mut writer1,
+            &mut writer2,
+        )
+        .unwrap();
+    let ids1 = ids(&writer1.into_inner());
+    assert!(!ids1.is_empty());
+    assert_eq!(ids1, ids(&writer2.into_inner()));
+}
+
+#[test]
+fn test_sample_reservoir() {
+    let data = fastq(100, 1);
+    let sampler = Sampler::new(7);
+    let sampled = sampler.reservoir(&mut records(&data), 10).unwrap();
+    assert_eq!(sampled.len(), 10);
+    assert_eq!(sampled, sampler.reservoir(&mut records(&data), 10).unwrap());
+    // records are in the input order
+    let indices: Vec<usize> = sampled
+        .iter()
+        .map(|r| r.head[1..r.head.len() - 2].parse().unwrap())
+        .collect();
+    assert!(indices.windows(2).all(|w| w[0] < w[1]));
+    // fewer records than the reservoir
+    assert_eq!(
+        sampler
+            .reservoir(&mut records(&fastq(3, 1)), 10)
+            .unwrap()
+            .len(),
+        3
+    );
+
+    let pairs = sampler
+        .reservoir_paired(&mut records(&data), &mut records(&fastq(100, 2)), 10)
+        .unwrap();
+    assert_eq!(pairs.len(), 10);
+    for ((r1, r2), r) in pairs.iter().zip(&sampled) {
+        assert_eq!(r1, r);
+        assert_eq!(
+            r1.head.trim_end_matches("/1"),
+            r2.head.trim_end_matches("/2")
+        );
+    }
+    assert!(sampler
+        .reservoir_paired(&mut records(&data), &mut records(&fastq(99, 2)), 10)
+        .is_err());
+}
+
+#[test]
+fn test_sample_exact() {
+    let data1 = fastq(100, 1);
+    let data2 = fastq(100, 2);
+    let path1 = temp_file("1.fq", &data1);
+    let path2 = temp_file("2.fq", &data2);
+    let sampler = Sampler::new(3);
+
+    let mut writer = Writer::new(Vec::new());
+    assert_eq!(sampler.exact(&path1, 10, &mut writer).unwrap(), 10);
+    let expected: Vec<String> = sampler
+        .reservoir(&mut records(&data1), 10)
+        .unwrap()
+        .iter()
+        .map(|r| r.head.trim_end_matches("/1").to_string())
+        .collect();
+    assert_eq!(ids(&writer.into_inner()), expected);
+
+    let mut writer1 = Writer::new(Vec::new());
+    let mut writer2 = Writer::new(Vec::new());
+    assert_eq!(
+        sampler
+            .exact_paired(&path1, &path2, 10, &mut writer1, &mut writer2)
+            .unwrap(),
+        10
+    );
+    assert_eq!(ids(&writer1.into_inner()), expected);
+    assert_eq!(ids(&writer2.into_inner()), expected);
+    fs::remove_file(path1).unwrap();
+    fs::remove_file(path2).unwrap();
+}
@@ -0,0 +1,82 @@
+use kseq::seq;
+use std::io::Cursor;
+
+#[test]
+fn test_revcomp() {
+    assert_eq!(seq::revcomp(b"ACGTacgtN"), b"NacgtACGT");
+    assert_eq!(seq::revcomp(b"RYKMSWBDHVN"), b"NBDHVWSKMRY");
+    assert_eq!(seq::revcomp(b"ryk"), b"mry");
+    assert_eq!(seq::revcomp_rna(b"ACGUu"), b"aACGU");
+    let mut bases = b"AACGTt".to_vec();
+    seq::revcomp_in_place(&mut bases);
+    assert_eq!(bases, b"aACGTT");
+    assert_eq!(seq::reverse_qual(b"!#I"), b"I#!");
+}
+
+#[test]
+fn test_mask() {
+    assert!(seq::is_soft_masked(b"ACgtA"));
+    assert!(!seq::is_soft_masked(b"ACGTN"));
+    assert_eq!(
+        seq::soft_masked_regions(b"aCGttAc"),
+        vec![(0, 1), (3, 5), (6, 7)]
+    );
+
+    let mut bases = b"aCGttAc".to_vec();
+    seq::hard_mask(&mut bases);
+    assert_eq!(bases, b"NCGNNAN");
+    let mut bases = b"aCGttAc".to_vec();
+    seq::unmask(&mut bases);
+    assert_eq!(bases, b"ACGTTAC");
+    let mut bases = b"ACGT".to_vec();
+    seq::mask_low_qual(&mut bases, b"I#I!", 33, 20);
+    assert_eq!(bases, b"ANGN");
+}
+
+#[test]
+fn test_rna_dna() {
+    let mut bases = b"ATtGC".to_vec();
+    seq::to_rna(&mut bases);
+    assert_eq!(bases, b"AUuGC");
+    seq::to_dna(&mut bases);
+    assert_eq!(bases, b"ATtGC");
+}
+
+#[test]
+fn test_record_revcomp() {
+    let data = b"@1 r1\nAACGt\n+\n!#+5I\n".to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    let mut owned = seq::revcomp_record(&record);
+    assert_eq!(owned.head, "1");
+    assert_eq!(owned.des, " r1");
+    assert_eq!(owned.seq, "aCGTT");
+    assert_eq!(owned.qual, "I5+#!");
+
+    owned.revcomp();
+    assert_eq!(owned, record.to_owned_fastx());
+    owned.unmask();
+    owned.to_rna();
+    assert_eq!(owned.seq, "AACGU");
+    owned.mask_low_qual(33, 10);
+    assert_eq!(owned.seq, "NNCGU");
+}
+
+#[test]
+fn test_record_non_ascii() {
+    let mut owned = kseq::record::OwnedFastx {
+        head: "1".to_string(),
+        seq: "AéC".to_string(),
+        qual: "I!é".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(seq::revcomp_str(&owned.seq), "GéT");
+    owned.revcomp();
+    assert_eq!((owned.seq.as_str(), owned.qual.as_str()), ("GéT", "é!I"));
+    owned.mask_low_qual(33, 10);
+    owned.hard_mask();
+    assert_eq!(owned.seq, "GéT");
+    owned.unmask();
+    owned.to_rna();
+    assert_eq!(owned.seq, "GéT");
+}
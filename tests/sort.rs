@@ -0,0 +1,88 @@
+mod common;
+
+use common::records;
+use kseq::sort::{SortKey, Sorter};
+use std::fs;
+
+static DATA: &str = "@r3\nACGTAC\n+\nIIIIII\n@r1\nAC\n+\nII\n@r4\nTTTT\n+\nIIII\n\
+                     @r2\nGGGG\n+\nIIII\n@r5\nA\n+\nI\n";
+
+fn heads(sorter: &Sorter) -> (Vec<String>, usize) {
+    let sorted = sorter.sort(&mut records(DATA)).unwrap();
+    let runs = sorted.num_runs();
+    let heads = sorted.map(|r| r.unwrap().head).collect();
+    (heads, runs)
+}
+
+#[test]
+fn test_sort_in_memory() {
+    let mut sorter = Sorter::new(SortKey::Name);
+    assert_eq!(
+        heads(&sorter),
+        (
+            vec!["r1", "r2", "r3", "r4", "r5"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            0
+        )
+    );
+    sorter.reverse = true;
+    assert_eq!(heads(&sorter).0, vec!["r5", "r4", "r3", "r2", "r1"]);
+
+    // stable for equal keys
+    let sorter = Sorter::new(SortKey::Length);
+    assert_eq!(heads(&sorter).0, vec!["r5", "r1", "r4", "r2", "r3"]);
+    let sorter = Sorter::new(SortKey::Sequence);
+    assert_eq!(heads(&sorter).0, vec!["r5", "r1", "r3", "r2", "r4"]);
+
+    assert!(Sorter::new(SortKey::Name)
+        .sort(&mut records("@b\nAC\n+\nII\n>a\nACGT\n"))
+        .is_err());
+}
+
+#[test]
+fn test_sort_external() {
+    let dir = std::env::temp_dir().join(format!("kseq-sort-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let mut sorter = Sorter::new(SortKey::Length);
+    sorter.set_tmp_dir(&dir);
+    // the memory is at least 1 MB
+    sorter.set_memory(0);
+    assert_eq!(heads(&sorter).1, 0);
+
+    // about 330 bytes of memory for each record
+    let data: String = (0..12000)
+        .map(|i| {
+            format!(
+                "@{}\n{}\n+\n{}\n",
+                i,
+                "A".repeat(i % 50 + 75),
+                "I".repeat(i % 50 + 75)
+            )
+        })
+        .collect();
+    sorter.set_batch_size(2);
+    let sorted = sorter.sort(&mut records(&data)).unwrap();
+    assert!(sorted.num_runs() > 2);
+    // the runs are merged into 2 runs in a private directory
+    let tmp_dirs: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(tmp_dirs.len(), 1);
+    assert_eq!(fs::read_dir(&tmp_dirs[0]).unwrap().count(), 2);
+    let sorted: Vec<_> = sorted.map(|r| r.unwrap()).collect();
+    assert_eq!(sorted.len(), 12000);
+    // stable for equal keys
+    assert!(sorted.windows(2).all(|w| {
+        let (a, b) = (&w[0], &w[1]);
+        a.seq.len() < b.seq.len()
+            || (a.seq.len() == b.seq.len()
+                && a.head.parse::<usize>().unwrap() < b.head.parse::<usize>().unwrap())
+    }));
+    assert!(sorted.iter().all(|r| r.seq.len() == r.qual.len()));
+    // temporary files are removed
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{records, temp_dir};
+use kseq::split::{SplitBy, Splitter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+static DATA: &str =
+    "@r1 sample=A\nACGT\n+\nIIII\n@r2 sample=B\nAC\n+\nII\n@r3\nACGTAC\n+\nIIIIII\n\
+                     @r4 sample=A\nA\n+\nI\n@r5 sample=B\nACG\n+\nIII\n";
+
+fn heads(path: &PathBuf) -> Vec<String> {
+    let mut records = kseq::parse_path(path).unwrap();
+    let mut heads = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        heads.push(record.head().to_string());
+    }
+    heads
+}
+
+fn split(by: SplitBy, dir: &Path, template: &str) -> Vec<Vec<String>> {
+    let template = dir.join(template);
+    let paths = Splitter::new(by, template.to_str().unwrap())
+        .run(&mut records(DATA))
+        .unwrap();
+    paths.iter().map(heads).collect()
+}
+
+#[test]
+fn test_split_parts() {
+    let dir = temp_dir("parts");
+    assert_eq!(
+        split(SplitBy::Records(2), &dir, "r.{part}.{ext}"),
+        vec![vec!["r1", "r2"], vec!["r3", "r4"], vec!["r5"]]
+    );
+    assert!(dir.join("r.003.fq").exists());
+    assert_eq!(
+        split(SplitBy::Parts(2), &dir, "p.{part}.fq.gz"),
+        vec![vec!["r1", "r3", "r5"], vec!["r2", "r4"]]
+    );
+    assert_eq!(
+        split(SplitBy::Bases(6), &dir, "b.{part}.fq"),
+        vec![vec!["r1", "r2"], vec!["r3"], vec!["r4", "r5"]]
+    );
+    // the template must contain {part}
+    assert!(
+        Splitter::new(SplitBy::Records(2), dir.join("x.fq").to_str().unwrap())
+            .run(&mut records(DATA))
+            .is_err()
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_key() {
+    let dir = temp_dir("key");
+    let mut parts = split(SplitBy::des_tag("sample="), &dir, "{key}.fq");
+    parts.sort();
+    assert_eq!(parts, vec![vec!["r1", "r4"], vec!["r2", "r5"], vec!["r3"]]);
+    assert!(dir.join("unmatched.fq").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_max_open() {
+    let dir = temp_dir("max-open");
+    for template in ["{key}.fq", "{key}.fq.gz"] {
+        let mut splitter = Splitter::new(
+            SplitBy::des_tag("sample="),
+            dir.join(template).to_str().unwrap(),
+        );
+        splitter.set_max_open(1);
+        let mut paths = splitter.run(&mut records(DATA)).unwrap();
+        paths.sort();
+        let parts: Vec<Vec<String>> = paths.iter().map(heads).collect();
+        assert_eq!(parts, vec![vec!["r1", "r4"], vec!["r2", "r5"], vec!["r3"]]);
+    }
+
+    let template = dir.join("p.{part}_R{mate}.fq");
+    let mut splitter = Splitter::new(SplitBy::Parts(2), template.to_str().unwrap());
+    splitter.set_max_open(1);
+    let paths = splitter
+        .run_paired(&mut records(DATA), &mut records(DATA))
+        .unwrap();
+    assert_eq!(paths.len(), 4);
+    assert_eq!(heads(&dir.join("p.001_R2.fq")), vec!["r1", "r3", "r5"]);
+    assert_eq!(heads(&dir.join("p.002_R1.fq")), vec!["r2", "r4"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_unsafe_key() {
+    let dir = temp_dir("unsafe-key");
+    let data = "@r1 sample=\nA\n+\nI\n@r2 sample=..\nA\n+\nI\n@r3 sample=a/b\nA\n+\nI\n";
+    let template = dir.join("{key}.fq");
+    let mut paths = Splitter::new(SplitBy::des_tag("sample="), template.to_str().unwrap())
+        .run(&mut records(data))
+        .unwrap();
+    paths.sort();
+    assert_eq!(
+        paths,
+        vec![
+            dir.join("__.fq"),
+            dir.join("a_b.fq"),
+            dir.join("unmatched.fq")
+        ]
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_paired() {
+    let dir = temp_dir("paired");
+    let template = dir.join("{part}_R{mate}.fq.gz");
+    let paths = Splitter::new(SplitBy::Bases(16), template.to_str().unwrap())
+        .run_paired(&mut records(DATA), &mut records(DATA))
+        .unwrap();
+    assert_eq!(paths.len(), 4);
+    assert_eq!(heads(&dir.join("001_R1.fq.gz")), vec!["r1", "r2", "r3"]);
+    assert_eq!(heads(&dir.join("001_R2.fq.gz")), vec!["r1", "r2", "r3"]);
+    assert_eq!(heads(&dir.join("002_R2.fq.gz")), vec!["r4", "r5"]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_split_single_then_paired() {
+    let dir = temp_dir("single_paired");
+    let template = dir.join("{part}_R{mate}.fq");
+    let mut splitter = Splitter::new(SplitBy::Parts(1), template.to_str().unwrap());
+    let mut paths1 = records(DATA);
+    let mut paths2 = records(DATA);
+    let r1 = paths1.iter_record().unwrap().unwrap();
+    splitter.write(&r1).unwrap();
+    let r1 = paths1.iter_record().unwrap().unwrap();
+    paths2.iter_record().unwrap().unwrap();
+    let r2 = paths2.iter_record().unwrap().unwrap();
+    splitter.write_pair(&r1, &r2).unwrap();
+    let paths = splitter.finish().unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(heads(&dir.join("001_R1.fq")), vec!["r1", "r2"]);
+    assert_eq!(heads(&dir.join("001_R2.fq")), vec!["r2"]);
+
+    // the template of single records may not be used for pairs
+    let template = dir.join("{part}.fq");
+    let mut splitter = Splitter::new(SplitBy::Parts(1), template.to_str().unwrap());
+    let r1 = paths1.iter_record().unwrap().unwrap();
+    splitter.write(&r1).unwrap();
+    let r1 = paths1.iter_record().unwrap().unwrap();
+    assert!(splitter.write_pair(&r1, &r1).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
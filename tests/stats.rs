@@ -0,0 +1,77 @@
+use kseq::stats::{Stats, Summary};
+use std::io::Cursor;
+
+fn stats(data: &str) -> Stats {
+    let mut records = kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap();
+    Stats::from_paths(&mut records).unwrap()
+}
+
+#[test]
+fn test_fasta_stats() {
+    let stats = stats(">1\nACGTN\n>2\nGGGGGGGGGG\nCC\n>3\nAT\n>4\nacgtnnnn\n");
+    let summary = stats.summary();
+    assert_eq!(summary.num_seqs, 4);
+    assert_eq!(summary.num_bases, 27);
+    assert_eq!(summary.min_len, 2);
+    assert_eq!(summary.max_len, 12);
+    assert_eq!(summary.mean_len, 6.75);
+    assert_eq!(summary.n50, 8);
+    assert_eq!(summary.n90, 5);
+    assert_eq!(summary.num_n, 5);
+    assert!((summary.gc_content - 1600.0 / 27.0).abs() < 1e-9);
+    assert_eq!(summary.q20, 0.0);
+}
+
+#[test]
+fn test_fastq_stats() {
+    let summary = stats("@1\nACGT\n+\n!5?I\n@2\nAC\n+\n+5\n").summary();
+    assert_eq!(summary.num_seqs, 2);
+    assert!((summary.q20 - 400.0 / 6.0).abs() < 1e-9);
+    assert!((summary.q30 - 200.0 / 6.0).abs() < 1e-9);
+    assert_eq!(
+        summary.to_string(),
+        "2\t6\t2\t3.0\t4\t4\t2\t0\t50.00\t66.67\t33.33"
+    );
+    assert_eq!(
+        Summary::tsv_header().split('\t').count(),
+        summary.to_string().split('\t').count()
+    );
+}
+
+#[test]
+fn test_empty_stats() {
+    let summary = Stats::new().summary();
+    assert_eq!(summary.num_seqs, 0);
+    assert_eq!(summary.min_len, 0);
+    assert_eq!(summary.n50, 0);
+    assert_eq!(summary.mean_len, 0.0);
+}
+
+#[test]
+fn test_merge_stats() {
+    let data = "@1\nACGT\n+\n!5?I\n@2\nAC\n+\n+5\n@3\nGGGGGG\n+\nIIIIII\n";
+    let whole = stats(data);
+
+    let mut records = kseq::parse_reader(Cursor::new(data.as_bytes().to_vec())).unwrap();
+    let mut parts = [Stats::new(), Stats::new()];
+    let mut n = 0;
+    while let Some(record) = records.iter_record().unwrap() {
+        parts[n % 2].update(&record);
+        n += 1;
+    }
+    let mut merged = Stats::new();
+    parts.iter().for_each(|part| merged.merge(part));
+    assert_eq!(merged, whole);
+    assert_eq!(merged.summary(), whole.summary());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_stats() {
+    let stats = stats(">1\nACGTN\n>2\nGG\n");
+    let json = serde_json::to_string(&stats).unwrap();
+    let parsed: Stats = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, stats);
+    let json = serde_json::to_string(&stats.summary()).unwrap();
+    assert!(json.contains("\"n50\":5"));
+}
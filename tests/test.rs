@@ -1,3 +1,5 @@
+#![allow(clippy::single_component_path_imports, clippy::useless_format)]
+
 use kseq;
 use std::io::Cursor;
 use std::result::Result;
@@ -219,7 +221,7 @@ fn test_invalid_fastq_seq_has_diff_len_with_qual() {
     .into_bytes();
     assert_err!(
         count_base(data),
-        Err(kseq::record::ParseError::InvalidFastx(_))
+        Err(kseq::record::ParseError::InvalidFastq(_))
     );
 }
 
@@ -237,6 +239,254 @@ fn test_invalid_fastq_with_seq_len_is_0() {
     );
 }
 
+#[test]
+fn test_crlf_fasta() {
+    let data: Vec<u8> = format!(
+        ">1 record1\r\n{seq}\r\n{seq}\n>2 record2\n{seq}\r\n\r\n>3 record3\r\n{seq}\r",
+        seq = BASE_SEQ
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let mut seqs = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        assert!(!record.des().contains('\r'));
+        seqs.push(record.seq().to_string());
+    }
+    assert_eq!(
+        seqs,
+        vec![
+            BASE_SEQ.repeat(2),
+            BASE_SEQ.to_string(),
+            BASE_SEQ.to_string()
+        ]
+    );
+}
+
+#[test]
+fn test_crlf_fastq() {
+    let data: Vec<u8> = format!(
+        "@1 record1\r\n{seq}\r\n{seq}\r\n+\r\n{qual}\r\n{qual}\r\n@2 record2\n{seq}\n+1\r\n{qual}\r\n",
+        seq = BASE_SEQ,
+        qual = BASE_QUAL
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.des(), " record1");
+    assert_eq!(record.seq(), BASE_SEQ.repeat(2));
+    assert_eq!(record.sep(), "+");
+    assert_eq!(record.qual(), BASE_QUAL.repeat(2));
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.sep(), "+1");
+    assert_eq!(record.qual(), BASE_QUAL);
+    assert!(records.iter_record().unwrap().is_none());
+}
+
+#[test]
+fn test_invalid_cr_line_ending() {
+    let data: Vec<u8> =
+        format!(">1 record1\r{seq}\r>2 record2\r{seq}\r", seq = BASE_SEQ).into_bytes();
+    assert_err!(
+        count_base(data),
+        Err(kseq::record::ParseError::InvalidLineEnding(_))
+    );
+
+    // only the first line is checked, a `\r` inside a later line is kept
+    let data = b"@1\nAC\rG\n+\nIIII\n>2\nAC\rGT\n".to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    assert_eq!(records.iter_record().unwrap().unwrap().seq(), "AC\rG");
+    assert_eq!(records.iter_record().unwrap().unwrap().seq(), "AC\rGT");
+}
+
+#[test]
+fn test_fastq_qual_starts_with_at_and_plus() {
+    let data: Vec<u8> = format!(
+        "@1 record1\n{seq}\n{seq}\n+\n@{qual}\n+{qual}\n@2 record2\n{seq}\n+\n@{qual}\n@3 record3\n{seq}\n+\n+{qual}\n",
+        seq = BASE_SEQ,
+        qual = &BASE_QUAL[1..]
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let mut heads = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        assert_eq!(record.seq().len(), record.qual().len());
+        assert_eq!(record.sep(), "+");
+        heads.push(record.head().to_string());
+    }
+    assert_eq!(heads, vec!["1", "2", "3"]);
+}
+
+#[test]
+fn test_fastq_unequal_line_widths() {
+    let data: Vec<u8> = format!(
+        "@1 record1\n{seq}\nAC\n{seq}\n+1 record1\n@@@\n{qual}\n\n@@@@@@@@@@@\n@2 record2\n{seq}\n+\n{qual}\n",
+        seq = BASE_SEQ,
+        qual = BASE_QUAL
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.seq(), format!("{seq}AC{seq}", seq = BASE_SEQ));
+    assert_eq!(record.sep(), "+1 record1");
+    assert_eq!(record.qual(), "@".repeat(BASE_SEQ.len() * 2 + 2));
+    let record = records.iter_record().unwrap().unwrap();
+    assert_eq!(record.head(), "2");
+    assert!(records.iter_record().unwrap().is_none());
+}
+
+#[test]
+fn test_single_line_fastq() {
+    let data: Vec<u8> = format!(
+        "@1 record1\n{seq}\n+\n{qual}\n@2 record2\n{seq}\n+2 record2\n{qual}",
+        seq = BASE_SEQ,
+        qual = BASE_QUAL
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    records.set_single_line(true);
+    let mut n = 0;
+    while let Some(record) = records.iter_record().unwrap() {
+        assert_eq!(record.seq(), BASE_SEQ);
+        assert_eq!(record.qual(), BASE_QUAL);
+        n += 1;
+    }
+    assert_eq!(n, 2);
+
+    let data: Vec<u8> = format!(
+        "@1 record1\n{seq}\n{seq}\n+\n{qual}",
+        seq = BASE_SEQ,
+        qual = BASE_QUAL
+    )
+    .into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    records.set_single_line(true);
+    assert_err!(
+        records.iter_record().map(|_| ()),
+        Err(kseq::record::ParseError::InvalidFastq(_))
+    );
+
+    // the third line is not a separator
+    let mut records = kseq::parse_reader(Cursor::new(b"@1\nA\nC\n+\nI\n".to_vec())).unwrap();
+    records.set_single_line(true);
+    assert_err!(
+        records.iter_record().map(|_| ()),
+        Err(kseq::record::ParseError::InvalidFastq(_))
+    );
+}
+
+#[test]
+fn test_record_lines() {
+    let data = b"@3\nAC\nGT\n+\nII\nII\n>1 record1\r\nACGT\r\nAC\r\n\r\nGG\n>2\nTTTT\nCC\n>3\nGA\n"
+        .to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let lines = |records: &mut kseq::Paths| {
+        let mut record = records.iter_record_lines().unwrap().unwrap();
+        let head = record.head().to_string();
+        let mut lines = Vec::new();
+        while let Some(seq_lines) = record.next_lines().unwrap() {
+            lines.extend(seq_lines.map(|line| String::from_utf8(line.to_vec()).unwrap()));
+        }
+        (head, lines)
+    };
+    assert_eq!(
+        lines(&mut records),
+        ("3".to_string(), vec!["ACGT".to_string()])
+    );
+    assert_eq!(
+        lines(&mut records),
+        (
+            "1".to_string(),
+            vec!["ACGT".to_string(), "AC".to_string(), "GG".to_string()]
+        )
+    );
+    // the rest of an unfinished record is skipped
+    let mut record = records.iter_record_lines().unwrap().unwrap();
+    assert_eq!(
+        record.next_lines().unwrap().unwrap().next(),
+        Some(&b"TTTT"[..])
+    );
+    assert_eq!(records.iter_record().unwrap().unwrap().seq(), "GA");
+    assert!(records.iter_record_lines().unwrap().is_none());
+
+    // a line longer than the buffer is split
+    let seq = BASE_SEQ.repeat(10000);
+    let data = format!(">1\n{}\n>2\nA\n", seq).into_bytes();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let (_, split) = lines(&mut records);
+    assert!(split.len() > 1 && split.iter().all(|line| !line.is_empty()));
+    assert_eq!(split.concat(), seq);
+
+    let mut records = kseq::parse_reader(Cursor::new(b">1\n\n>2\nA\n".to_vec())).unwrap();
+    let mut record = records.iter_record_lines().unwrap().unwrap();
+    assert_err!(
+        record.next_lines().map(|_| ()),
+        Err(kseq::record::ParseError::InvalidFasta(_))
+    );
+    let mut records = kseq::parse_reader(Cursor::new(b">1\nACGT\n>2\n".to_vec())).unwrap();
+    assert_eq!(lines(&mut records).1.concat(), "ACGT");
+    let mut record = records.iter_record_lines().unwrap().unwrap();
+    assert_err!(
+        record.next_lines().map(|_| ()),
+        Err(kseq::record::ParseError::TruncateFile(_))
+    );
+}
+
+#[test]
+fn test_record_chunks() {
+    let data =
+        b"@0 fq\nACGTA\n+\nIIIII\n>1 record1\r\nACGT\r\nAC\r\n\r\nGG\n>2\nTTTT\nCC\n>3\nGA\n"
+            .to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let chunks = |records: &mut kseq::Paths| {
+        let mut record = records.iter_record_chunks(3).unwrap().unwrap();
+        let head = record.head().to_string();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = record.next_chunk().unwrap() {
+            chunks.push(chunk.to_string());
+        }
+        assert_eq!(
+            record.num_bases(),
+            chunks.iter().map(String::len).sum::<usize>()
+        );
+        (head, chunks)
+    };
+    assert_eq!(
+        chunks(&mut records),
+        ("0".to_string(), vec!["ACG".to_string(), "TA".to_string()])
+    );
+    assert_eq!(
+        chunks(&mut records),
+        (
+            "1".to_string(),
+            vec!["ACG".to_string(), "TAC".to_string(), "GG".to_string()]
+        )
+    );
+    // the rest of an unfinished record is skipped
+    let mut record = records.iter_record_chunks(3).unwrap().unwrap();
+    assert_eq!(record.next_chunk().unwrap(), Some("TTT"));
+    assert_eq!(records.iter_record().unwrap().unwrap().seq(), "GA");
+    assert!(records.iter_record_chunks(3).unwrap().is_none());
+
+    let mut records = kseq::parse_reader(Cursor::new(b">1\n>2\nA\n".to_vec())).unwrap();
+    let mut record = records.iter_record_chunks(3).unwrap().unwrap();
+    assert_err!(
+        record.next_chunk().map(|_| ()),
+        Err(kseq::record::ParseError::InvalidFasta(_))
+    );
+
+    // chunks are cut at character boundaries
+    let data = "@1\nAéG€\n+\nIIIIIII\n>2\nAé\nG€\n".as_bytes().to_vec();
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    for _ in 0..2 {
+        let mut record = records.iter_record_chunks(2).unwrap().unwrap();
+        let mut chunks = Vec::new();
+        while let Some(chunk) = record.next_chunk().unwrap() {
+            chunks.push(chunk.to_string());
+        }
+        assert_eq!(chunks, vec!["A", "é", "G", "€"]);
+    }
+}
+
 // #[test]
 // fn test_large_fasta() {
 //     let count = 1_000_000;
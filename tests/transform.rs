@@ -0,0 +1,103 @@
+mod common;
+
+use kseq::record::{FastxRecord, OwnedFastx};
+use kseq::transform::{Crop, MaxNFraction, MinLen, Pipeline, PolyTrim, QualityTrim, Transform};
+use std::io::Cursor;
+
+#[test]
+fn test_quality_trim() {
+    let mut trim = QualityTrim::new(2, 20, 33);
+    let record = trim
+        .apply(common::record("1", "", "AACCGGTT", "##II5I##"))
+        .unwrap();
+    // a passing window is kept as a whole
+    assert_eq!(record.seq, "ACCGGT");
+    assert_eq!(record.qual, "#II5I#");
+
+    trim.front = false;
+    let record = trim
+        .apply(common::record("1", "", "AACCGGTT", "##II5I##"))
+        .unwrap();
+    assert_eq!(record.seq, "AACCGGT");
+
+    let record = trim.apply(common::record("1", "", "AACC", "####")).unwrap();
+    assert!(record.is_empty());
+    assert!(record.qual.is_empty());
+}
+
+#[test]
+fn test_crop() {
+    let record = Crop { head: 2, tail: 3 }
+        .apply(common::record("1", "", "AACCGGTT", "ABCDEFGH"))
+        .unwrap();
+    assert_eq!(record.seq, "CCG");
+    assert_eq!(record.qual, "CDE");
+    let record = Crop { head: 5, tail: 5 }
+        .apply(common::record("1", "", "AACCGGTT", "ABCDEFGH"))
+        .unwrap();
+    assert_eq!(record.seq, "");
+}
+
+#[test]
+fn test_poly_trim() {
+    let mut trim = PolyTrim::new(b'G', 10);
+    let record = trim
+        .apply(common::record(
+            "1",
+            "",
+            "ACGTACGTGGGGGAGGGGGG",
+            "IIIIIIIIIIIIIIIIIIII",
+        ))
+        .unwrap();
+    assert_eq!(record.seq, "ACGTACGT");
+    assert_eq!(record.qual, "IIIIIIII");
+    // too short to be trimmed
+    let record = trim
+        .apply(common::record("1", "", "ACGTACGTGGGG", "IIIIIIIIIIII"))
+        .unwrap();
+    assert_eq!(record.seq, "ACGTACGTGGGG");
+}
+
+#[test]
+fn test_filters() {
+    assert!(MinLen(5)
+        .apply(common::record("1", "", "ACGT", "IIII"))
+        .is_none());
+    assert!(MinLen(4)
+        .apply(common::record("1", "", "ACGT", "IIII"))
+        .is_some());
+    assert!(MaxNFraction(0.25)
+        .apply(common::record("1", "", "ACNN", "IIII"))
+        .is_none());
+    assert!(MaxNFraction(0.5)
+        .apply(common::record("1", "", "ACNn", "IIII"))
+        .is_some());
+}
+
+#[test]
+fn test_pipeline() {
+    let data = b"@1\nAAAAACCCCCCCCCGGGGGGGGGGGGG\n+\n#####IIIIIIIIIIIIIIIIIIIIII\n\
+                 @2\nAAAAACCC\n+\n#####III\n>3\nACGTNNNNNN\n"
+        .to_vec();
+    let mut pipeline = Pipeline::new()
+        .then(QualityTrim::new(3, 20, 33))
+        .then(PolyTrim::new(b'G', 10))
+        .then(MinLen(6))
+        .then(|mut record: OwnedFastx| {
+            record.head.push_str("/trimmed");
+            Some(record)
+        });
+    let mut records = kseq::parse_reader(Cursor::new(data)).unwrap();
+    let mut kept = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        if let Some(record) = pipeline.apply(&record) {
+            kept.push(record);
+        }
+    }
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].head, "1/trimmed");
+    assert_eq!(kept[0].seq, "ACCCCCCCCC");
+    assert_eq!(kept[0].qual, "#IIIIIIIII");
+    assert!(kept[1].is_fasta());
+    assert_eq!(kept[1].seq, "ACGTNNNNNN");
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::record;
+use kseq::transform::{Pipeline, Transform};
+use kseq::umi::{UmiExtractor, UmiPlacement};
+
+#[test]
+fn test_umi_extract() {
+    let mut extractor = UmiExtractor::new("NNNXXNN").unwrap();
+    let extracted = extractor
+        .apply(record("r1", " 1:N", "ACGTTCAGGG", "ABCDEFGHIJ"))
+        .unwrap();
+    assert_eq!(extracted, record("r1_ACGCA", " 1:N", "TTGGG", "DEHIJ"));
+    // too short reads are discarded
+    assert!(extractor.apply(record("r2", "", "ACGT", "IIII")).is_none());
+    // so are non-ASCII reads, whose bases can't be cut by bytes
+    assert!(extractor
+        .apply(record("r3", "", "AéGTTCAGG", "ABCDEFGHIJ"))
+        .is_none());
+    assert!(extractor
+        .apply(record("r3", "", "ACGTTCAGGG", "ABCDEFGHIé"))
+        .is_none());
+
+    extractor.placement = UmiPlacement::Tag;
+    extractor.three_prime = true;
+    let mut pipeline = Pipeline::new().then(extractor);
+    assert_eq!(
+        pipeline.apply_owned(record("r1", "", "GGGACGTTCA", "ABCDEFGHIJ")),
+        Some(record("r1", " RX:Z:ACGCA", "GGGTT", "ABCGH"))
+    );
+    assert!(UmiExtractor::new("NNNY").is_err());
+}
+
+#[test]
+fn test_umi_extract_pair() {
+    let mut extractor = UmiExtractor::new("NNN").unwrap();
+    let (r1, r2) = extractor
+        .apply_pair(
+            record("r1", "", "ACGTTT", "IIIIII"),
+            record("r1", "", "GGGCCC", "IIIIII"),
+        )
+        .unwrap();
+    assert_eq!(r1, record("r1_ACG", "", "TTT", "III"));
+    assert_eq!(r2, record("r1_ACG", "", "GGGCCC", "IIIIII"));
+
+    extractor.set_mate_pattern("NNX").unwrap();
+    let (r1, r2) = extractor
+        .apply_pair(
+            record("r1", "", "ACGTTT", "IIIIII"),
+            record("r1", "", "GGGCCC", "IIIIII"),
+        )
+        .unwrap();
+    assert_eq!(r1.head, "r1_ACGGG");
+    assert_eq!(r2, record("r1_ACGGG", "", "GCCC", "IIII"));
+    assert!(extractor
+        .apply_pair(
+            record("r1", "", "ACGTTT", "IIIIII"),
+            record("r1", "", "G", "I")
+        )
+        .is_none());
+}
@@ -0,0 +1,45 @@
+use kseq::writer::{create_path, Writer};
+use std::io::Cursor;
+
+static DATA: &str = "@1 record1\nACGT\nACGT\n+1\nIIII\nIIII\n>2\nAC\nGT\n";
+
+#[test]
+fn test_write_records() {
+    let mut records = kseq::parse_reader(Cursor::new(DATA.as_bytes().to_vec())).unwrap();
+    let mut writer = Writer::new(Vec::new());
+    while let Some(record) = records.iter_record().unwrap() {
+        writer.write_record(&record).unwrap();
+    }
+    let mut records = kseq::parse_reader(Cursor::new(DATA.as_bytes().to_vec())).unwrap();
+    let record = records.iter_record().unwrap().unwrap();
+    writer.write_fasta(&record).unwrap();
+    let mut owned = record.to_owned_fastx();
+    owned.sep.clear();
+    writer.write_fastq(&owned).unwrap();
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        "@1 record1\nACGTACGT\n+1\nIIIIIIII\n>2\nACGT\n\
+         >1 record1\nACGTACGT\n@1 record1\nACGTACGT\n+\nIIIIIIII\n"
+    );
+}
+
+#[test]
+fn test_write_gz_path() {
+    let path = std::env::temp_dir().join(format!("kseq-writer-{}.fq.gz", std::process::id()));
+    {
+        let mut records = kseq::parse_reader(Cursor::new(DATA.as_bytes().to_vec())).unwrap();
+        let mut writer = create_path(&path).unwrap();
+        while let Some(record) = records.iter_record().unwrap() {
+            writer.write_record(&record).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    assert_eq!(&std::fs::read(&path).unwrap()[..2], b"\x1f\x8b");
+    let mut records = kseq::parse_path(&path).unwrap();
+    let mut heads = Vec::new();
+    while let Some(record) = records.iter_record().unwrap() {
+        heads.push(format!("{}:{}", record.head(), record.seq()));
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(heads, vec!["1:ACGTACGT", "2:ACGT"]);
+}